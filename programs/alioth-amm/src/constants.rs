@@ -22,6 +22,31 @@ pub const DEFAULT_ORACLE_DEVIATION_BPS: u64 = 500;
 /// Maximum basis points (100%)
 pub const MAX_BPS: u64 = 10000;
 
+/// Emergency pause: everything except withdrawals, so LPs can always exit. It is bit 0 so a
+/// pool paused through the old `is_paused` bool (stored as 1) stays emergency-paused.
+pub const PAUSE_EMERGENCY: u8 = 1 << 0;
+
+/// Pause flag for swaps (including multi-hop and split swaps)
+pub const PAUSE_SWAPS: u8 = 1 << 1;
+
+/// Pause flag for liquidity deposits
+pub const PAUSE_DEPOSITS: u8 = 1 << 2;
+
+/// Pause flag for liquidity withdrawals
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 3;
+
+/// Pause flag for flash loans
+pub const PAUSE_FLASH_LOANS: u8 = 1 << 4;
+
+/// Operations stopped by `PAUSE_EMERGENCY`
+pub const EMERGENCY_PAUSED_OPERATIONS: u8 = PAUSE_SWAPS | PAUSE_DEPOSITS | PAUSE_FLASH_LOANS;
+
+/// Full freeze: every operation, including withdrawals
+pub const PAUSE_ALL: u8 = PAUSE_EMERGENCY | EMERGENCY_PAUSED_OPERATIONS | PAUSE_WITHDRAWALS;
+
+/// Maximum length of the justification given for a full freeze
+pub const MAX_FREEZE_REASON_LEN: usize = 200;
+
 /// Maximum number of hops in multi-hop swap
 pub const MAX_SWAP_HOPS: u8 = 3;
 
@@ -40,6 +65,12 @@ pub const POOL_SEED: &[u8] = b"pool";
 /// LP mint seed prefix
 pub const LP_MINT_SEED: &[u8] = b"lp_mint";
 
+/// Locked minimum liquidity account seed prefix
+pub const LP_LOCK_SEED: &[u8] = b"lp_lock";
+
+/// Token Metadata account seed prefix (owned by the Metaplex program)
+pub const METADATA_SEED: &[u8] = b"metadata";
+
 /// Token A vault seed prefix
 pub const TOKEN_A_VAULT_SEED: &[u8] = b"token_a_vault";
 
@@ -61,9 +92,27 @@ pub const USER_STAKE_SEED: &[u8] = b"user_stake";
 /// Flash loan record seed prefix
 pub const FLASH_LOAN_SEED: &[u8] = b"flash_loan";
 
-/// Minimum farming duration in slots (approximately 1 hour at 400ms per slot)
-pub const MIN_FARMING_DURATION: u64 = 9000;
+/// Temporary wSOL account seed prefix, per user; the account never outlives an instruction
+pub const WSOL_TEMP_SEED: &[u8] = b"wsol_temp";
+
+/// Minimum farming duration in seconds (1 hour)
+pub const MIN_FARMING_DURATION: i64 = 3600;
+
+/// Maximum farming duration in seconds (30 days)
+pub const MAX_FARMING_DURATION: i64 = 2_592_000;
+
+
+/// Maximum number of parallel pools in a split-route swap
+pub const MAX_SPLIT_POOLS: u8 = 4;
+
+/// Prefix of LP token names, e.g. "ALIOTH-SOL/USDC-LP"
+pub const LP_TOKEN_NAME_PREFIX: &str = "ALIOTH";
+
+/// Symbol shared by every pool's LP token
+pub const LP_TOKEN_SYMBOL: &str = "ALIOTH-LP";
 
-/// Maximum farming duration in slots (approximately 30 days)
-pub const MAX_FARMING_DURATION: u64 = 6_480_000;
+/// Maximum token name length accepted by Token Metadata
+pub const MAX_METADATA_NAME_LEN: usize = 32;
 
+/// Maximum URI length accepted by Token Metadata
+pub const MAX_METADATA_URI_LEN: usize = 200;
@@ -85,5 +85,50 @@ pub enum AmmError {
     
     #[msg("Numerical overflow in calculation")]
     NumericalOverflow,
+    
+    #[msg("Reward payout exceeds funded rewards")]
+    InsufficientRewardFunding,
+    
+    #[msg("Farming period has not ended")]
+    FarmingNotEnded,
+    
+    #[msg("Farm still has staked LP tokens")]
+    FarmNotDrained,
+    
+    #[msg("Stake account still holds staked LP tokens")]
+    StakeNotEmpty,
+    
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    
+    #[msg("Price impact exceeds maximum allowed")]
+    PriceImpactExceeded,
+    
+    #[msg("Pausing withdrawals requires a full freeze with a reason")]
+    WithdrawalPauseRequiresFreeze,
+    
+    #[msg("Freeze reason must be non-empty and at most 200 bytes")]
+    InvalidFreezeReason,
+    
+    #[msg("LP token name must be at most 32 bytes and its URI at most 200 bytes")]
+    InvalidLpMetadata,
+    
+    #[msg("Pool still has LP tokens outstanding beyond the locked minimum")]
+    OutstandingLiquidity,
+    
+    #[msg("The LP lock account is required for a pool's first deposit")]
+    MissingLpLock,
+    
+    #[msg("Native SOL requires the temporary wSOL account and the native mint")]
+    MissingNativeSolAccount,
+    
+    #[msg("Swap needs the user's output account or a recipient output account")]
+    MissingOutputAccount,
+    
+    #[msg("Pool is frozen; only unpause_pool can lift a freeze")]
+    PoolFrozen,
+    
+    #[msg("A user token account required by this instruction was not provided")]
+    MissingTokenAccount,
 }
 
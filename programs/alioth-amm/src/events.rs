@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when a pool is fully frozen, including withdrawals
+#[event]
+pub struct PoolFrozen {
+    /// Pool that was frozen
+    pub pool: Pubkey,
+
+    /// Authority that froze the pool
+    pub authority: Pubkey,
+
+    /// Justification given by the authority
+    pub reason: String,
+
+    /// Unix timestamp of the freeze
+    pub timestamp: i64,
+}
+
+/// Emitted when vault balances above the reserves are skimmed
+#[event]
+pub struct PoolSkimmed {
+    /// Pool that was skimmed
+    pub pool: Pubkey,
+
+    /// Account that received the token A surplus
+    pub recipient_token_a: Pubkey,
+
+    /// Account that received the token B surplus
+    pub recipient_token_b: Pubkey,
+
+    /// Token A surplus transferred
+    pub amount_a: u64,
+
+    /// Token B surplus transferred
+    pub amount_b: u64,
+}
+
+/// Emitted when the authority resets reserves to the vault balances
+#[event]
+pub struct ReservesSynced {
+    /// Pool that was synced
+    pub pool: Pubkey,
+
+    /// Authority that ran the sync
+    pub authority: Pubkey,
+
+    /// Token A reserve before the sync
+    pub old_reserve_a: u64,
+
+    /// Token B reserve before the sync
+    pub old_reserve_b: u64,
+
+    /// Token A reserve after the sync
+    pub new_reserve_a: u64,
+
+    /// Token B reserve after the sync
+    pub new_reserve_b: u64,
+
+    /// Change in token A reserve (positive when the vault held a surplus)
+    pub delta_a: i128,
+
+    /// Change in token B reserve (positive when the vault held a surplus)
+    pub delta_b: i128,
+
+    /// Unix timestamp of the sync
+    pub timestamp: i64,
+}
+
+/// Emitted when the authority retires a pool
+#[event]
+pub struct PoolClosed {
+    /// Pool that was closed
+    pub pool: Pubkey,
+
+    /// Authority that closed the pool and received the rent
+    pub authority: Pubkey,
+
+    /// Token A swept from the vault
+    pub swept_a: u64,
+
+    /// Token B swept from the vault
+    pub swept_b: u64,
+
+    /// Unix timestamp of the close
+    pub timestamp: i64,
+}
@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, MintTo, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount, MintTo, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
 use crate::state::{Pool, LiquidityProvider};
-use crate::utils::AmmMath;
+use crate::utils::{AmmMath, NativeSolHelper};
 
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
@@ -34,19 +34,21 @@ pub struct AddLiquidity<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// May be omitted when token A is wSOL and `native_sol` is set
     #[account(
         mut,
         constraint = user_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
         constraint = user_token_a.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_a: Account<'info, TokenAccount>,
+    pub user_token_a: Option<Account<'info, TokenAccount>>,
 
+    /// May be omitted when token B is wSOL and `native_sol` is set
     #[account(
         mut,
         constraint = user_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
         constraint = user_token_b.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_b: Account<'info, TokenAccount>,
+    pub user_token_b: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -66,6 +68,21 @@ pub struct AddLiquidity<'info> {
     )]
     pub lp_mint: Account<'info, Mint>,
 
+    /// Receives the permanently locked `MINIMUM_LIQUIDITY`; only required on the first deposit.
+    /// Pools created before the lock existed get it here, paid for by the first depositor.
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [
+            LP_LOCK_SEED,
+            pool.key().as_ref(),
+        ],
+        bump,
+        token::mint = lp_mint,
+        token::authority = pool,
+    )]
+    pub lp_lock: Option<Account<'info, TokenAccount>>,
+
     #[account(
         init_if_needed,
         payer = user,
@@ -74,57 +91,98 @@ pub struct AddLiquidity<'info> {
     )]
     pub user_lp_token: Account<'info, TokenAccount>,
 
+    /// Temporary wSOL account for `native_sol`, created here and closed before the instruction ends
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            WSOL_TEMP_SEED,
+            user.key().as_ref(),
+        ],
+        bump,
+        token::mint = native_mint,
+        token::authority = user,
+    )]
+    pub wsol_temp: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Account<'info, Mint>>,
+
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+/// Deposit the largest amounts within `amount_a_desired`/`amount_b_desired` that match the
+/// pool's reserve ratio; only those amounts are transferred
 pub fn handler(
     ctx: Context<AddLiquidity>,
-    amount_a: u64,
-    amount_b: u64,
-    min_liquidity: u64,
+    amount_a_desired: u64,
+    amount_b_desired: u64,
+    amount_a_min: u64,
+    amount_b_min: u64,
+    native_sol: bool,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
     // Check if pool is paused
-    require!(!pool.is_paused, AmmError::PoolPaused);
+    pool.require_not_paused(PAUSE_DEPOSITS)?;
 
     // Validate amounts
+    require!(amount_a_desired > 0 && amount_b_desired > 0, AmmError::ZeroAmount);
+
+    // Calculate the ratio-optimal deposit, checking slippage on the trimmed side
+    let (amount_a, amount_b) = AmmMath::calculate_optimal_deposit(
+        amount_a_desired,
+        amount_b_desired,
+        amount_a_min,
+        amount_b_min,
+        pool.reserve_a,
+        pool.reserve_b,
+    )?;
     require!(amount_a > 0 && amount_b > 0, AmmError::ZeroAmount);
 
-    // Calculate liquidity to mint
-    let liquidity = if pool.total_lp_supply == 0 {
-        // First liquidity provision
-        let initial_liquidity = AmmMath::calculate_initial_liquidity(amount_a, amount_b)?;
-        
-        // Check minimum liquidity
-        require!(
-            initial_liquidity >= MINIMUM_LIQUIDITY,
-            AmmError::MinimumLiquidityNotMet
-        );
+    // Update pool state and calculate liquidity to mint
+    let (liquidity, locked_liquidity) = pool.record_deposit(amount_a, amount_b)?;
+    require!(liquidity > 0, AmmError::ZeroAmount);
 
-        initial_liquidity - MINIMUM_LIQUIDITY
-    } else {
-        // Subsequent liquidity provision
-        AmmMath::calculate_liquidity(
-            amount_a,
-            amount_b,
-            pool.reserve_a,
-            pool.reserve_b,
-            pool.total_lp_supply,
-        )?
-    };
-
-    // Check slippage tolerance
-    require!(liquidity >= min_liquidity, AmmError::SlippageExceeded);
+    // With native_sol, the wSOL side is funded from lamports through the temporary account
+    NativeSolHelper::require_native_pair(native_sol, &pool.token_a_mint, &pool.token_b_mint)?;
+    let source_a = NativeSolHelper::user_account(
+        native_sol,
+        &pool.token_a_mint,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.wsol_temp,
+    )?
+    .to_account_info();
+    let source_b = NativeSolHelper::user_account(
+        native_sol,
+        &pool.token_b_mint,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.wsol_temp,
+    )?
+    .to_account_info();
+    if native_sol {
+        let amount = if NativeSolHelper::is_native(&pool.token_a_mint) {
+            amount_a
+        } else {
+            amount_b
+        };
+        NativeSolHelper::wrap(
+            &ctx.accounts.system_program,
+            &ctx.accounts.token_program,
+            &ctx.accounts.user,
+            NativeSolHelper::temp_account(&ctx.accounts.wsol_temp)?,
+            amount,
+        )?;
+    }
 
     // Transfer tokens from user to pool
     let transfer_a_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.user_token_a.to_account_info(),
+            from: source_a,
             to: ctx.accounts.token_a_vault.to_account_info(),
             authority: ctx.accounts.user.to_account_info(),
         },
@@ -134,7 +192,7 @@ pub fn handler(
     let transfer_b_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.user_token_b.to_account_info(),
+            from: source_b,
             to: ctx.accounts.token_b_vault.to_account_info(),
             authority: ctx.accounts.user.to_account_info(),
         },
@@ -161,14 +219,29 @@ pub fn handler(
     );
     token::mint_to(mint_ctx, liquidity)?;
 
-    // Update pool state
-    pool.reserve_a = pool.reserve_a.checked_add(amount_a).unwrap();
-    pool.reserve_b = pool.reserve_b.checked_add(amount_b).unwrap();
-    pool.total_lp_supply = pool.total_lp_supply.checked_add(liquidity).unwrap();
+    // Lock minimum liquidity in the pool-owned account on the first deposit
+    if locked_liquidity > 0 {
+        let lp_lock = ctx.accounts.lp_lock.as_ref().ok_or(AmmError::MissingLpLock)?;
+        let lock_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: lp_lock.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token::mint_to(lock_ctx, locked_liquidity)?;
+    }
 
     // Update TWAP
     pool.update_twap(clock.unix_timestamp)?;
 
+    // The temporary wSOL account is empty again; return its rent
+    if let Some(wsol_temp) = &ctx.accounts.wsol_temp {
+        NativeSolHelper::close_temp_account(&ctx.accounts.token_program, wsol_temp, &ctx.accounts.user)?;
+    }
+
     // Update or initialize LP provider state
     let lp_provider = &mut ctx.accounts.lp_provider;
     if lp_provider.lp_token_amount == 0 {
@@ -179,11 +252,14 @@ pub fn handler(
         lp_provider.created_at = clock.unix_timestamp;
         lp_provider.bump = ctx.bumps.lp_provider;
     }
-    lp_provider.lp_token_amount = lp_provider.lp_token_amount.checked_add(liquidity).unwrap();
+    lp_provider.record_deposit(liquidity, amount_a, amount_b, pool.fee_growth_index()?)?;
 
     msg!("Liquidity added successfully");
     msg!("Amount A: {}, Amount B: {}", amount_a, amount_b);
     msg!("LP tokens minted: {}", liquidity);
+    if locked_liquidity > 0 {
+        msg!("LP tokens locked: {}", locked_liquidity);
+    }
 
     Ok(())
 }
@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
+use anchor_spl::metadata::{self, Metadata, MetadataAccount, UpdateMetadataAccountsV2};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, SetAuthority, Token, TokenAccount, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
+use crate::events::{PoolClosed, PoolFrozen};
 use crate::state::Pool;
+use crate::utils::TokenMetadataHelper;
 
 // ========== Pause Pool ==========
 
@@ -22,12 +27,16 @@ pub struct PausePool<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Emergency pause: stops swaps, deposits and flash loans while withdrawals stay open
 pub fn pause_pool_handler(ctx: Context<PausePool>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
 
-    require!(!pool.is_paused, AmmError::PoolPaused);
+    require!(
+        pool.pause_flags & PAUSE_EMERGENCY != PAUSE_EMERGENCY,
+        AmmError::PoolPaused
+    );
 
-    pool.is_paused = true;
+    pool.pause_flags |= PAUSE_EMERGENCY;
 
     msg!("Pool paused successfully");
     msg!("Pool: {}", pool.key());
@@ -54,12 +63,13 @@ pub struct UnpausePool<'info> {
     pub authority: Signer<'info>,
 }
 
+/// Clear every pause flag, including a full freeze
 pub fn unpause_pool_handler(ctx: Context<UnpausePool>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
 
-    require!(pool.is_paused, AmmError::InvalidPoolConfig);
+    require!(pool.pause_flags != 0, AmmError::InvalidPoolConfig);
 
-    pool.is_paused = false;
+    pool.pause_flags = 0;
 
     msg!("Pool unpaused successfully");
     msg!("Pool: {}", pool.key());
@@ -67,6 +77,91 @@ pub fn unpause_pool_handler(ctx: Context<UnpausePool>) -> Result<()> {
     Ok(())
 }
 
+// ========== Set Pause Flags ==========
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Pause an exact set of operations. Withdrawals can only be paused through `freeze_pool`,
+/// and a frozen pool can only be released through `unpause_pool`.
+pub fn set_pause_flags_handler(ctx: Context<SetPauseFlags>, pause_flags: u8) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Overwriting the flags would silently lift a freeze
+    require!(pool.pause_flags & PAUSE_WITHDRAWALS == 0, AmmError::PoolFrozen);
+
+    require!(pause_flags & !PAUSE_ALL == 0, AmmError::InvalidPoolConfig);
+    require!(
+        pause_flags & PAUSE_WITHDRAWALS == 0,
+        AmmError::WithdrawalPauseRequiresFreeze
+    );
+
+    let old_pause_flags = pool.pause_flags;
+    pool.pause_flags = pause_flags;
+
+    msg!("Pool pause flags updated successfully");
+    msg!("Old flags: {:#07b}, New flags: {:#07b}", old_pause_flags, pause_flags);
+
+    Ok(())
+}
+
+// ========== Freeze Pool ==========
+
+#[derive(Accounts)]
+pub struct FreezePool<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Full freeze including withdrawals; the justification is recorded in a `PoolFrozen` event
+pub fn freeze_pool_handler(ctx: Context<FreezePool>, reason: String) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    require!(
+        !reason.trim().is_empty() && reason.len() <= MAX_FREEZE_REASON_LEN,
+        AmmError::InvalidFreezeReason
+    );
+
+    pool.pause_flags = PAUSE_ALL;
+
+    emit!(PoolFrozen {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        reason,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Pool frozen");
+    msg!("Pool: {}", pool.key());
+
+    Ok(())
+}
+
 // ========== Update Fees ==========
 
 #[derive(Accounts)]
@@ -196,3 +291,246 @@ pub fn update_oracle_config_handler(
     Ok(())
 }
 
+// ========== Update LP Metadata URI ==========
+
+#[derive(Accounts)]
+pub struct UpdateLpMetadataUri<'info> {
+    #[account(
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+
+    /// Metaplex metadata of the pool's LP mint
+    #[account(
+        mut,
+        seeds = [
+            METADATA_SEED,
+            metadata::ID.as_ref(),
+            pool.lp_mint.as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub lp_metadata: Box<Account<'info, MetadataAccount>>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+/// Point the LP token metadata at a new URI; name and symbol are kept
+pub fn update_lp_metadata_uri_handler(ctx: Context<UpdateLpMetadataUri>, uri: String) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let data = TokenMetadataHelper::lp_token_data(
+        TokenMetadataHelper::stored_name(&ctx.accounts.lp_metadata),
+        uri.clone(),
+    )?;
+
+    let update_metadata_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        UpdateMetadataAccountsV2 {
+            metadata: ctx.accounts.lp_metadata.to_account_info(),
+            update_authority: pool.to_account_info(),
+        },
+        signer,
+    );
+    metadata::update_metadata_accounts_v2(update_metadata_ctx, None, Some(data), None, None)?;
+
+    msg!("LP metadata URI updated successfully");
+    msg!("URI: {}", uri);
+
+    Ok(())
+}
+
+// ========== Close Pool ==========
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ AmmError::Unauthorized,
+        close = authority
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ AmmError::InvalidPoolConfig,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    /// Absent for pools created before the lock existed, which never minted locked liquidity
+    #[account(
+        mut,
+        seeds = [
+            LP_LOCK_SEED,
+            pool.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub lp_lock: Option<Account<'info, TokenAccount>>,
+
+    /// Receives the token A left in the vault
+    #[account(
+        mut,
+        constraint = recipient_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub recipient_token_a: Account<'info, TokenAccount>,
+
+    /// Receives the token B left in the vault
+    #[account(
+        mut,
+        constraint = recipient_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub recipient_token_b: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Retire a pool once only the locked minimum liquidity is left: sweep the vaults, close
+/// them and the lock account, and remove the LP mint authority so no LP can ever be minted.
+///
+/// Retirement is permanent for the pair: the LP mint and its metadata PDAs outlive the pool,
+/// so `initialize_pool` can never recreate a pool for the same two mints.
+pub fn close_pool_handler(ctx: Context<ClosePool>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let locked_liquidity = ctx
+        .accounts
+        .lp_lock
+        .as_ref()
+        .map_or(0, |lp_lock| lp_lock.amount);
+
+    // Every LP token in existence must be the locked one
+    require!(
+        pool.total_lp_supply == locked_liquidity && ctx.accounts.lp_mint.supply == locked_liquidity,
+        AmmError::OutstandingLiquidity
+    );
+
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Burn the locked liquidity so the lock account can be closed
+    if let Some(lp_lock) = &ctx.accounts.lp_lock {
+        if locked_liquidity > 0 {
+            let burn_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: lp_lock.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                signer,
+            );
+            token::burn(burn_ctx, locked_liquidity)?;
+        }
+    }
+
+    // Sweep what is left in the vaults, including the reserves behind the locked liquidity
+    let swept_a = ctx.accounts.token_a_vault.amount;
+    let swept_b = ctx.accounts.token_b_vault.amount;
+    for (vault, recipient, amount) in [
+        (&ctx.accounts.token_a_vault, &ctx.accounts.recipient_token_a, swept_a),
+        (&ctx.accounts.token_b_vault, &ctx.accounts.recipient_token_b, swept_b),
+    ] {
+        if amount > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: vault.to_account_info(),
+                    to: recipient.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(transfer_ctx, amount)?;
+        }
+    }
+
+    // Close the vaults and the lock account, returning their rent to the authority
+    let mut accounts_to_close = vec![
+        ctx.accounts.token_a_vault.to_account_info(),
+        ctx.accounts.token_b_vault.to_account_info(),
+    ];
+    if let Some(lp_lock) = &ctx.accounts.lp_lock {
+        accounts_to_close.push(lp_lock.to_account_info());
+    }
+    for account in accounts_to_close {
+        let close_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account,
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token::close_account(close_ctx)?;
+    }
+
+    // Disable the LP mint for good
+    let set_authority_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        SetAuthority {
+            current_authority: pool.to_account_info(),
+            account_or_mint: ctx.accounts.lp_mint.to_account_info(),
+        },
+        signer,
+    );
+    token::set_authority(set_authority_ctx, AuthorityType::MintTokens, None)?;
+
+    emit!(PoolClosed {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        swept_a,
+        swept_b,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Pool closed successfully");
+    msg!("Swept A: {}, Swept B: {}", swept_a, swept_b);
+
+    // The pool account will be closed automatically via the close constraint
+
+    Ok(())
+}
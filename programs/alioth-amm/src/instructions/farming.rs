@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
 use crate::state::{Pool, FarmingPool, UserStake};
@@ -60,21 +60,21 @@ pub struct InitializeFarm<'info> {
 
 pub fn initialize_farm_handler(
     ctx: Context<InitializeFarm>,
-    reward_per_slot: u64,
-    start_slot: u64,
-    end_slot: u64,
+    reward_per_second: u64,
+    start_time: i64,
+    end_time: i64,
 ) -> Result<()> {
     let farming_pool = &mut ctx.accounts.farming_pool;
     let clock = Clock::get()?;
 
     // Validate farming parameters
-    require!(reward_per_slot > 0, AmmError::InvalidPoolConfig);
-    require!(start_slot >= clock.slot, AmmError::InvalidPoolConfig);
-    require!(end_slot > start_slot, AmmError::InvalidPoolConfig);
+    require!(reward_per_second > 0, AmmError::InvalidPoolConfig);
+    require!(start_time >= clock.unix_timestamp, AmmError::InvalidPoolConfig);
+    require!(end_time > start_time, AmmError::InvalidPoolConfig);
 
-    let duration = end_slot.checked_sub(start_slot).unwrap();
+    let duration = end_time.checked_sub(start_time).ok_or(AmmError::MathOverflow)?;
     require!(
-        duration >= MIN_FARMING_DURATION && duration <= MAX_FARMING_DURATION,
+        (MIN_FARMING_DURATION..=MAX_FARMING_DURATION).contains(&duration),
         AmmError::InvalidPoolConfig
     );
 
@@ -85,18 +85,79 @@ pub fn initialize_farm_handler(
     farming_pool.reward_mint = ctx.accounts.reward_mint.key();
     farming_pool.reward_vault = ctx.accounts.reward_vault.key();
     farming_pool.total_staked = 0;
-    farming_pool.reward_per_slot = reward_per_slot;
-    farming_pool.start_slot = start_slot;
-    farming_pool.end_slot = end_slot;
-    farming_pool.last_update_slot = start_slot;
+    farming_pool.reward_per_second = reward_per_second;
+    farming_pool.start_time = start_time;
+    farming_pool.end_time = end_time;
+    farming_pool.last_update_time = start_time;
     farming_pool.accumulated_reward_per_share = 0;
     farming_pool.total_rewards_distributed = 0;
+    farming_pool.total_rewards_funded = 0;
+    farming_pool.total_rewards_claimed = 0;
     farming_pool.is_active = true;
     farming_pool.bump = ctx.bumps.farming_pool;
 
     msg!("Farming pool initialized successfully");
-    msg!("Reward per slot: {}", reward_per_slot);
-    msg!("Duration: {} slots", duration);
+    msg!("Reward per second: {}", reward_per_second);
+    msg!("Duration: {} seconds", duration);
+
+    Ok(())
+}
+
+// ========== Fund Farm ==========
+
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            farming_pool.pool.as_ref(),
+        ],
+        bump = farming_pool.bump,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = funder_reward_token.mint == farming_pool.reward_mint @ AmmError::TokenMintMismatch,
+        constraint = funder_reward_token.owner == funder.key() @ AmmError::InvalidAuthority,
+    )]
+    pub funder_reward_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reward_vault.key() == farming_pool.reward_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn fund_farm_handler(ctx: Context<FundFarm>, amount: u64) -> Result<()> {
+    let farming_pool = &mut ctx.accounts.farming_pool;
+
+    // Validate amount
+    require!(amount > 0, AmmError::ZeroAmount);
+
+    // Transfer reward tokens from funder to vault
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder_reward_token.to_account_info(),
+            to: ctx.accounts.reward_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    // Only funded rewards can ever accrue to stakers
+    farming_pool.record_funding(amount)?;
+
+    msg!("Farm funded successfully");
+    msg!("Amount: {}", amount);
+    msg!("Total rewards funded: {}", farming_pool.total_rewards_funded);
 
     Ok(())
 }
@@ -152,10 +213,31 @@ pub struct Stake<'info> {
     #[account(
         mut,
         constraint = lp_token_vault.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = lp_token_vault.owner == farming_pool.key() @ AmmError::InvalidAuthority,
     )]
     pub lp_token_vault: Account<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        constraint = reward_vault.key() == farming_pool.reward_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = reward_mint.key() == farming_pool.reward_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward_token: Account<'info, TokenAccount>,
+
     pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
@@ -166,11 +248,11 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
 
     // Check farming period
     require!(
-        clock.slot >= farming_pool.start_slot,
+        clock.unix_timestamp >= farming_pool.start_time,
         AmmError::FarmingNotStarted
     );
     require!(
-        clock.slot < farming_pool.end_slot,
+        clock.unix_timestamp < farming_pool.end_time,
         AmmError::FarmingEnded
     );
 
@@ -178,14 +260,11 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
     require!(amount > 0, AmmError::ZeroAmount);
 
     // Update farming pool rewards
-    farming_pool.update_rewards(clock.slot)?;
+    farming_pool.update_rewards(clock.unix_timestamp)?;
 
     // If user has existing stake, claim pending rewards first
     if user_stake.staked_amount > 0 {
-        let pending_rewards = farming_pool.calculate_pending_rewards(
-            user_stake.staked_amount,
-            user_stake.reward_debt,
-        )?;
+        let pending_rewards = farming_pool.settle_rewards(user_stake)?;
 
         if pending_rewards > 0 {
             // Transfer rewards to user
@@ -199,24 +278,22 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
             let transfer_ctx = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: ctx.accounts.lp_token_vault.to_account_info(),
-                    to: ctx.accounts.user_lp_token.to_account_info(),
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.user_reward_token.to_account_info(),
                     authority: farming_pool.to_account_info(),
                 },
                 signer,
             );
             token::transfer(transfer_ctx, pending_rewards)?;
 
-            user_stake.total_rewards_claimed = user_stake.total_rewards_claimed
-                .checked_add(pending_rewards)
-                .unwrap();
+            user_stake.last_claim_time = clock.unix_timestamp;
         }
     } else {
         // Initialize user stake
         user_stake.owner = ctx.accounts.user.key();
         user_stake.farming_pool = farming_pool.key();
         user_stake.created_at = clock.unix_timestamp;
-        user_stake.last_claim_slot = clock.slot;
+        user_stake.last_claim_time = clock.unix_timestamp;
         user_stake.total_rewards_claimed = 0;
         user_stake.bump = ctx.bumps.user_stake;
     }
@@ -232,12 +309,8 @@ pub fn stake_handler(ctx: Context<Stake>, amount: u64) -> Result<()> {
     );
     token::transfer(transfer_ctx, amount)?;
 
-    // Update user stake
-    user_stake.staked_amount = user_stake.staked_amount.checked_add(amount).unwrap();
-    user_stake.update_reward_debt(farming_pool.accumulated_reward_per_share);
-
-    // Update farming pool
-    farming_pool.total_staked = farming_pool.total_staked.checked_add(amount).unwrap();
+    // Update user stake and farming pool
+    farming_pool.add_stake(user_stake, amount)?;
 
     msg!("LP tokens staked successfully");
     msg!("Amount staked: {}", amount);
@@ -295,6 +368,7 @@ pub struct Unstake<'info> {
     #[account(
         mut,
         constraint = lp_token_vault.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = lp_token_vault.owner == farming_pool.key() @ AmmError::InvalidAuthority,
     )]
     pub lp_token_vault: Account<'info, TokenAccount>,
 
@@ -332,13 +406,10 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
     );
 
     // Update farming pool rewards
-    farming_pool.update_rewards(clock.slot)?;
+    farming_pool.update_rewards(clock.unix_timestamp)?;
 
     // Calculate and claim pending rewards
-    let pending_rewards = farming_pool.calculate_pending_rewards(
-        user_stake.staked_amount,
-        user_stake.reward_debt,
-    )?;
+    let pending_rewards = farming_pool.settle_rewards(user_stake)?;
 
     if pending_rewards > 0 {
         // Transfer rewards to user
@@ -360,10 +431,7 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
         );
         token::transfer(transfer_reward_ctx, pending_rewards)?;
 
-        user_stake.total_rewards_claimed = user_stake.total_rewards_claimed
-            .checked_add(pending_rewards)
-            .unwrap();
-        user_stake.last_claim_slot = clock.slot;
+        user_stake.last_claim_time = clock.unix_timestamp;
     }
 
     // Transfer LP tokens back to user
@@ -385,12 +453,8 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
     );
     token::transfer(transfer_lp_ctx, amount)?;
 
-    // Update user stake
-    user_stake.staked_amount = user_stake.staked_amount.checked_sub(amount).unwrap();
-    user_stake.update_reward_debt(farming_pool.accumulated_reward_per_share);
-
-    // Update farming pool
-    farming_pool.total_staked = farming_pool.total_staked.checked_sub(amount).unwrap();
+    // Update user stake and farming pool
+    farming_pool.remove_stake(user_stake, amount)?;
 
     msg!("LP tokens unstaked successfully");
     msg!("Amount unstaked: {}", amount);
@@ -399,6 +463,91 @@ pub fn unstake_handler(ctx: Context<Unstake>, amount: u64) -> Result<()> {
     Ok(())
 }
 
+// ========== Emergency Unstake ==========
+
+#[derive(Accounts)]
+pub struct EmergencyUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            farming_pool.pool.as_ref(),
+        ],
+        bump = farming_pool.bump,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_STAKE_SEED,
+            farming_pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = user_lp_token.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_token_vault.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = lp_token_vault.owner == farming_pool.key() @ AmmError::InvalidAuthority,
+    )]
+    pub lp_token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Withdraw the full stake and forfeit pending rewards.
+/// Deliberately skips the farm's schedule and active flag, and tolerates broken reward math,
+/// so principal can always be recovered. Forfeited rewards return to the farm's unallocated
+/// budget and are emitted to the remaining stakers.
+pub fn emergency_unstake_handler(ctx: Context<EmergencyUnstake>) -> Result<()> {
+    let farming_pool = &mut ctx.accounts.farming_pool;
+    let user_stake = &mut ctx.accounts.user_stake;
+    let clock = Clock::get()?;
+
+    require!(user_stake.staked_amount > 0, AmmError::InsufficientStake);
+
+    // Accrue rewards to everyone at the current stake before this one leaves
+    let amount = farming_pool.emergency_remove_stake(user_stake, clock.unix_timestamp);
+
+    // Transfer LP tokens back to user
+    let seeds = &[
+        FARMING_POOL_SEED,
+        farming_pool.pool.as_ref(),
+        &[farming_pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_lp_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.lp_token_vault.to_account_info(),
+            to: ctx.accounts.user_lp_token.to_account_info(),
+            authority: farming_pool.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_lp_ctx, amount)?;
+
+    msg!("Emergency unstake completed");
+    msg!("Amount unstaked: {}", amount);
+    msg!("Pending rewards forfeited");
+
+    Ok(())
+}
+
 // ========== Claim Rewards ==========
 
 #[derive(Accounts)]
@@ -465,13 +614,10 @@ pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
     let clock = Clock::get()?;
 
     // Update farming pool rewards
-    farming_pool.update_rewards(clock.slot)?;
+    farming_pool.update_rewards(clock.unix_timestamp)?;
 
-    // Calculate pending rewards
-    let pending_rewards = farming_pool.calculate_pending_rewards(
-        user_stake.staked_amount,
-        user_stake.reward_debt,
-    )?;
+    // Settle pending rewards
+    let pending_rewards = farming_pool.settle_rewards(user_stake)?;
 
     require!(pending_rewards > 0, AmmError::NoRewards);
 
@@ -495,11 +641,7 @@ pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
     token::transfer(transfer_ctx, pending_rewards)?;
 
     // Update user stake
-    user_stake.total_rewards_claimed = user_stake.total_rewards_claimed
-        .checked_add(pending_rewards)
-        .unwrap();
-    user_stake.last_claim_slot = clock.slot;
-    user_stake.update_reward_debt(farming_pool.accumulated_reward_per_share);
+    user_stake.last_claim_time = clock.unix_timestamp;
 
     msg!("Rewards claimed successfully");
     msg!("Amount: {}", pending_rewards);
@@ -507,3 +649,193 @@ pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
     Ok(())
 }
 
+
+// ========== Pause Farm ==========
+
+#[derive(Accounts)]
+pub struct PauseFarm<'info> {
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            farming_pool.pool.as_ref(),
+        ],
+        bump = farming_pool.bump,
+        constraint = farming_pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn pause_farm_handler(ctx: Context<PauseFarm>) -> Result<()> {
+    let farming_pool = &mut ctx.accounts.farming_pool;
+    let clock = Clock::get()?;
+
+    require!(farming_pool.is_active, AmmError::FarmingNotActive);
+
+    // Settle accrual up to the pause; nothing accrues while paused
+    farming_pool.update_rewards(clock.unix_timestamp)?;
+    farming_pool.is_active = false;
+
+    msg!("Farm paused successfully");
+    msg!("Farm: {}", farming_pool.key());
+
+    Ok(())
+}
+
+// ========== Resume Farm ==========
+
+#[derive(Accounts)]
+pub struct ResumeFarm<'info> {
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            farming_pool.pool.as_ref(),
+        ],
+        bump = farming_pool.bump,
+        constraint = farming_pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn resume_farm_handler(ctx: Context<ResumeFarm>) -> Result<()> {
+    let farming_pool = &mut ctx.accounts.farming_pool;
+    let clock = Clock::get()?;
+
+    require!(!farming_pool.is_active, AmmError::InvalidPoolConfig);
+
+    // Skip the paused period so it is never accrued retroactively
+    farming_pool.last_update_time = std::cmp::max(
+        farming_pool.last_update_time,
+        clock.unix_timestamp,
+    );
+    farming_pool.is_active = true;
+
+    msg!("Farm resumed successfully");
+    msg!("Farm: {}", farming_pool.key());
+
+    Ok(())
+}
+
+// ========== Close Farm ==========
+
+#[derive(Accounts)]
+pub struct CloseFarm<'info> {
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            farming_pool.pool.as_ref(),
+        ],
+        bump = farming_pool.bump,
+        constraint = farming_pool.authority == authority.key() @ AmmError::Unauthorized,
+        close = authority
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = reward_vault.key() == farming_pool.reward_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = authority_reward_token.mint == farming_pool.reward_mint @ AmmError::TokenMintMismatch,
+        constraint = authority_reward_token.owner == authority.key() @ AmmError::InvalidAuthority,
+    )]
+    pub authority_reward_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn close_farm_handler(ctx: Context<CloseFarm>) -> Result<()> {
+    let farming_pool = &ctx.accounts.farming_pool;
+    let clock = Clock::get()?;
+
+    // Farm must be finished (ended or paused) and hold no stake
+    require!(
+        clock.unix_timestamp >= farming_pool.end_time || !farming_pool.is_active,
+        AmmError::FarmingNotEnded
+    );
+    require!(farming_pool.total_staked == 0, AmmError::FarmNotDrained);
+
+    let seeds = &[
+        FARMING_POOL_SEED,
+        farming_pool.pool.as_ref(),
+        &[farming_pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // With nothing staked no rewards are owed, so any balance left is returned
+    let remaining_rewards = ctx.accounts.reward_vault.amount;
+    if remaining_rewards > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.authority_reward_token.to_account_info(),
+                authority: farming_pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, remaining_rewards)?;
+    }
+
+    // Close reward vault and return its rent to the authority
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.reward_vault.to_account_info(),
+            destination: ctx.accounts.authority.to_account_info(),
+            authority: farming_pool.to_account_info(),
+        },
+        signer,
+    );
+    token::close_account(close_ctx)?;
+
+    msg!("Farm closed successfully");
+    msg!("Remaining rewards returned: {}", remaining_rewards);
+
+    // The farming pool account will be closed automatically via the close constraint
+
+    Ok(())
+}
+
+// ========== Close User Stake ==========
+
+#[derive(Accounts)]
+pub struct CloseUserStake<'info> {
+    #[account(
+        mut,
+        seeds = [
+            USER_STAKE_SEED,
+            user_stake.farming_pool.as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == user.key() @ AmmError::InvalidAuthority,
+        constraint = user_stake.staked_amount == 0 @ AmmError::StakeNotEmpty,
+        close = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+pub fn close_user_stake_handler(ctx: Context<CloseUserStake>) -> Result<()> {
+    msg!("User stake closed successfully");
+    msg!("Stake: {}", ctx.accounts.user_stake.key());
+
+    // The user stake account will be closed automatically via the close constraint
+
+    Ok(())
+}
@@ -131,7 +131,7 @@ pub fn handler(
     let clock = Clock::get()?;
 
     // Check if pool is paused
-    require!(!pool.is_paused, AmmError::PoolPaused);
+    pool.require_not_paused(PAUSE_FLASH_LOANS)?;
 
     // Validate at least one amount is requested
     require!(amount_a > 0 || amount_b > 0, AmmError::ZeroAmount);
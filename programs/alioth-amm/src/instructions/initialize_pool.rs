@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata, MetadataAccount};
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::constants::*;
 use crate::errors::AmmError;
 use crate::state::Pool;
+use crate::utils::TokenMetadataHelper;
 
 #[derive(Accounts)]
 pub struct InitializePool<'info> {
@@ -25,6 +27,30 @@ pub struct InitializePool<'info> {
     pub token_a_mint: Account<'info, Mint>,
     pub token_b_mint: Account<'info, Mint>,
 
+    /// Metaplex metadata of token A, if it has any; its symbol names the LP token
+    #[account(
+        seeds = [
+            METADATA_SEED,
+            metadata::ID.as_ref(),
+            token_a_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub token_a_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// Metaplex metadata of token B, if it has any; its symbol names the LP token
+    #[account(
+        seeds = [
+            METADATA_SEED,
+            metadata::ID.as_ref(),
+            token_b_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub token_b_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
     #[account(
         init,
         payer = authority,
@@ -38,6 +64,34 @@ pub struct InitializePool<'info> {
     )]
     pub lp_mint: Account<'info, Mint>,
 
+    /// Pool-owned LP account holding the permanently locked `MINIMUM_LIQUIDITY`.
+    /// No instruction ever transfers or burns from it.
+    #[account(
+        init,
+        payer = authority,
+        seeds = [
+            LP_LOCK_SEED,
+            pool.key().as_ref(),
+        ],
+        bump,
+        token::mint = lp_mint,
+        token::authority = pool,
+    )]
+    pub lp_lock: Account<'info, TokenAccount>,
+
+    /// CHECK: Metaplex metadata PDA for the LP mint, created by the Token Metadata program
+    #[account(
+        mut,
+        seeds = [
+            METADATA_SEED,
+            metadata::ID.as_ref(),
+            lp_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+
     #[account(
         init,
         payer = authority,
@@ -73,6 +127,8 @@ pub struct InitializePool<'info> {
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
 }
 
 pub fn handler(
@@ -81,6 +137,7 @@ pub fn handler(
     fee_denominator: u64,
     oracle_max_age: i64,
     oracle_max_deviation_bps: u64,
+    lp_token_uri: String,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
@@ -110,6 +167,18 @@ pub fn handler(
         AmmError::InvalidPoolConfig
     );
 
+    // LP token name from the mints' own symbols, e.g. "ALIOTH-SOL/USDC-LP"
+    let lp_token_name = TokenMetadataHelper::lp_token_name(
+        &TokenMetadataHelper::pair_symbol(
+            ctx.accounts.token_a_metadata.as_deref().map(|metadata| &**metadata),
+            &ctx.accounts.token_a_mint.key(),
+        ),
+        &TokenMetadataHelper::pair_symbol(
+            ctx.accounts.token_b_metadata.as_deref().map(|metadata| &**metadata),
+            &ctx.accounts.token_b_mint.key(),
+        ),
+    )?;
+
     // Initialize pool state
     pool.authority = ctx.accounts.authority.key();
     pool.token_a_mint = ctx.accounts.token_a_mint.key();
@@ -126,7 +195,7 @@ pub fn handler(
     pool.oracle_b = ctx.accounts.oracle_b.key();
     pool.oracle_max_age = oracle_max_age;
     pool.oracle_max_deviation_bps = oracle_max_deviation_bps;
-    pool.is_paused = false;
+    pool.pause_flags = 0;
     pool.cumulative_price_a = 0;
     pool.cumulative_price_b = 0;
     pool.last_update_timestamp = clock.unix_timestamp;
@@ -136,9 +205,40 @@ pub fn handler(
     pool.total_fees_b = 0;
     pool.bump = ctx.bumps.pool;
 
+    // Create LP token metadata so wallets can display the pool token
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let create_metadata_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        CreateMetadataAccountsV3 {
+            metadata: ctx.accounts.lp_metadata.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            mint_authority: pool.to_account_info(),
+            payer: ctx.accounts.authority.to_account_info(),
+            update_authority: pool.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        },
+        signer,
+    );
+    metadata::create_metadata_accounts_v3(
+        create_metadata_ctx,
+        TokenMetadataHelper::lp_token_data(lp_token_name.clone(), lp_token_uri)?,
+        true,
+        true,
+        None,
+    )?;
+
     msg!("Pool initialized successfully");
     msg!("Token A: {}", pool.token_a_mint);
     msg!("Token B: {}", pool.token_b_mint);
+    msg!("LP token: {}", lp_token_name);
     msg!("Fee: {}%", (fee_numerator as f64 / fee_denominator as f64) * 100.0);
 
     Ok(())
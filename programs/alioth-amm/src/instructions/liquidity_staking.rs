@@ -0,0 +1,516 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::{FarmingPool, LiquidityProvider, Pool, UserStake};
+use crate::utils::AmmMath;
+
+// ========== Add Liquidity And Stake ==========
+
+/// Add liquidity and stake the minted LP tokens in a single instruction.
+/// LP tokens are minted straight into the farm's LP vault and never touch the user's wallet.
+#[derive(Accounts)]
+pub struct AddLiquidityAndStake<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LiquidityProvider::LEN,
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub lp_provider: Account<'info, LiquidityProvider>,
+
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            pool.key().as_ref(),
+        ],
+        bump = farming_pool.bump,
+        constraint = farming_pool.is_active @ AmmError::FarmingNotActive,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStake::LEN,
+        seeds = [
+            USER_STAKE_SEED,
+            farming_pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
+        constraint = user_token_a.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
+        constraint = user_token_b.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ AmmError::InvalidPoolConfig,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = lp_token_vault.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = lp_token_vault.owner == farming_pool.key() @ AmmError::InvalidAuthority,
+    )]
+    pub lp_token_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reward_vault.key() == farming_pool.reward_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = reward_mint.key() == farming_pool.reward_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Like `add_liquidity`, only the ratio-matching part of `amount_a_desired`/`amount_b_desired`
+/// is transferred
+pub fn add_liquidity_and_stake_handler(
+    ctx: Context<AddLiquidityAndStake>,
+    amount_a_desired: u64,
+    amount_b_desired: u64,
+    amount_a_min: u64,
+    amount_b_min: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let farming_pool = &mut ctx.accounts.farming_pool;
+    let user_stake = &mut ctx.accounts.user_stake;
+    let clock = Clock::get()?;
+
+    // Check if pool is paused
+    pool.require_not_paused(PAUSE_DEPOSITS)?;
+
+    // Check farming period
+    require!(
+        clock.unix_timestamp >= farming_pool.start_time,
+        AmmError::FarmingNotStarted
+    );
+    require!(
+        clock.unix_timestamp < farming_pool.end_time,
+        AmmError::FarmingEnded
+    );
+
+    // Validate amounts
+    require!(amount_a_desired > 0 && amount_b_desired > 0, AmmError::ZeroAmount);
+
+    // The first deposit must go through add_liquidity so the minimum liquidity gets locked
+    require!(pool.total_lp_supply > 0, AmmError::InsufficientLiquidity);
+
+    // Calculate the ratio-optimal deposit, checking slippage on the trimmed side
+    let (amount_a, amount_b) = AmmMath::calculate_optimal_deposit(
+        amount_a_desired,
+        amount_b_desired,
+        amount_a_min,
+        amount_b_min,
+        pool.reserve_a,
+        pool.reserve_b,
+    )?;
+    require!(amount_a > 0 && amount_b > 0, AmmError::ZeroAmount);
+
+    // Update pool state and calculate liquidity to mint
+    let (liquidity, _) = pool.record_deposit(amount_a, amount_b)?;
+    require!(liquidity > 0, AmmError::ZeroAmount);
+
+    // Transfer tokens from user to pool
+    let transfer_a_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.user_token_a.to_account_info(),
+            to: ctx.accounts.token_a_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::transfer(transfer_a_ctx, amount_a)?;
+
+    let transfer_b_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.user_token_b.to_account_info(),
+            to: ctx.accounts.token_b_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::transfer(transfer_b_ctx, amount_b)?;
+
+    // Update farming pool rewards before the stake changes
+    farming_pool.update_rewards(clock.unix_timestamp)?;
+
+    // If user has existing stake, claim pending rewards first
+    let mut pending_rewards = 0;
+    if user_stake.staked_amount > 0 {
+        pending_rewards = farming_pool.settle_rewards(user_stake)?;
+
+        if pending_rewards > 0 {
+            let farm_seeds = &[
+                FARMING_POOL_SEED,
+                farming_pool.pool.as_ref(),
+                &[farming_pool.bump],
+            ];
+            let farm_signer = &[&farm_seeds[..]];
+
+            let transfer_reward_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.user_reward_token.to_account_info(),
+                    authority: farming_pool.to_account_info(),
+                },
+                farm_signer,
+            );
+            token::transfer(transfer_reward_ctx, pending_rewards)?;
+
+            user_stake.last_claim_time = clock.unix_timestamp;
+        }
+    } else {
+        // Initialize user stake
+        user_stake.owner = ctx.accounts.user.key();
+        user_stake.farming_pool = farming_pool.key();
+        user_stake.created_at = clock.unix_timestamp;
+        user_stake.last_claim_time = clock.unix_timestamp;
+        user_stake.total_rewards_claimed = 0;
+        user_stake.bump = ctx.bumps.user_stake;
+    }
+
+    // Mint LP tokens directly into the farm's LP vault
+    let pool_seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let mint_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.lp_token_vault.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        pool_signer,
+    );
+    token::mint_to(mint_ctx, liquidity)?;
+
+    // Update TWAP
+    pool.update_twap(clock.unix_timestamp)?;
+
+    // Update or initialize LP provider state
+    let lp_provider = &mut ctx.accounts.lp_provider;
+    if lp_provider.lp_token_amount == 0 {
+        lp_provider.owner = ctx.accounts.user.key();
+        lp_provider.pool = pool.key();
+        lp_provider.initial_deposit_a = amount_a;
+        lp_provider.initial_deposit_b = amount_b;
+        lp_provider.created_at = clock.unix_timestamp;
+        lp_provider.bump = ctx.bumps.lp_provider;
+    }
+    lp_provider.record_deposit(liquidity, amount_a, amount_b, pool.fee_growth_index()?)?;
+
+    // Update user stake and farming pool
+    farming_pool.add_stake(user_stake, liquidity)?;
+
+    msg!("Liquidity added and staked successfully");
+    msg!("Amount A: {}, Amount B: {}", amount_a, amount_b);
+    msg!("LP tokens minted and staked: {}", liquidity);
+    msg!("Rewards claimed: {}", pending_rewards);
+
+    Ok(())
+}
+
+// ========== Unstake And Remove Liquidity ==========
+
+/// Unstake LP tokens and remove the underlying liquidity in a single instruction.
+/// LP tokens are burned straight out of the farm's LP vault.
+#[derive(Accounts)]
+pub struct UnstakeAndRemoveLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// Optional analytics record; redemption only depends on burning LP tokens
+    #[account(
+        mut,
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = lp_provider.bump,
+        constraint = lp_provider.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub lp_provider: Option<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        mut,
+        seeds = [
+            FARMING_POOL_SEED,
+            pool.key().as_ref(),
+        ],
+        bump = farming_pool.bump,
+    )]
+    pub farming_pool: Account<'info, FarmingPool>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_STAKE_SEED,
+            farming_pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
+        constraint = user_token_a.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_a: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
+        constraint = user_token_b.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_b: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ AmmError::InvalidPoolConfig,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = lp_token_vault.mint == farming_pool.lp_mint @ AmmError::TokenMintMismatch,
+        constraint = lp_token_vault.owner == farming_pool.key() @ AmmError::InvalidAuthority,
+    )]
+    pub lp_token_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = reward_vault.key() == farming_pool.reward_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = reward_mint.key() == farming_pool.reward_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn unstake_and_remove_liquidity_handler(
+    ctx: Context<UnstakeAndRemoveLiquidity>,
+    liquidity_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let farming_pool = &mut ctx.accounts.farming_pool;
+    let user_stake = &mut ctx.accounts.user_stake;
+    let clock = Clock::get()?;
+
+    // Check if pool is paused
+    pool.require_not_paused(PAUSE_WITHDRAWALS)?;
+
+    // Validate liquidity amount
+    require!(liquidity_amount > 0, AmmError::ZeroAmount);
+    require!(
+        user_stake.staked_amount >= liquidity_amount,
+        AmmError::InsufficientStake
+    );
+
+    // Update pool state and calculate amounts to withdraw
+    let (amount_a, amount_b) = pool.record_withdrawal(liquidity_amount)?;
+
+    // Check slippage tolerance
+    require!(amount_a >= min_amount_a, AmmError::SlippageExceeded);
+    require!(amount_b >= min_amount_b, AmmError::SlippageExceeded);
+
+    // Update farming pool rewards before the stake changes
+    farming_pool.update_rewards(clock.unix_timestamp)?;
+
+    // Calculate and claim pending rewards
+    let pending_rewards = farming_pool.settle_rewards(user_stake)?;
+
+    let farm_seeds = &[
+        FARMING_POOL_SEED,
+        farming_pool.pool.as_ref(),
+        &[farming_pool.bump],
+    ];
+    let farm_signer = &[&farm_seeds[..]];
+
+    if pending_rewards > 0 {
+        let transfer_reward_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.user_reward_token.to_account_info(),
+                authority: farming_pool.to_account_info(),
+            },
+            farm_signer,
+        );
+        token::transfer(transfer_reward_ctx, pending_rewards)?;
+
+        user_stake.last_claim_time = clock.unix_timestamp;
+    }
+
+    // Burn LP tokens straight from the farm's LP vault
+    let burn_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.lp_token_vault.to_account_info(),
+            authority: farming_pool.to_account_info(),
+        },
+        farm_signer,
+    );
+    token::burn(burn_ctx, liquidity_amount)?;
+
+    // Transfer tokens from pool to user
+    let pool_seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let pool_signer = &[&pool_seeds[..]];
+
+    let transfer_a_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.token_a_vault.to_account_info(),
+            to: ctx.accounts.user_token_a.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        pool_signer,
+    );
+    token::transfer(transfer_a_ctx, amount_a)?;
+
+    let transfer_b_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.token_b_vault.to_account_info(),
+            to: ctx.accounts.user_token_b.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        pool_signer,
+    );
+    token::transfer(transfer_b_ctx, amount_b)?;
+
+    // Update user stake and farming pool
+    farming_pool.remove_stake(user_stake, liquidity_amount)?;
+
+    // Update TWAP
+    pool.update_twap(clock.unix_timestamp)?;
+
+    // Update LP provider state
+    if let Some(lp_provider) = &mut ctx.accounts.lp_provider {
+        lp_provider.record_withdrawal(liquidity_amount, amount_a, amount_b);
+    }
+
+    msg!("Liquidity unstaked and removed successfully");
+    msg!("LP tokens unstaked and burned: {}", liquidity_amount);
+    msg!("Amount A: {}, Amount B: {}", amount_a, amount_b);
+    msg!("Rewards claimed: {}", pending_rewards);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::{LiquidityProvider, Pool};
+
+#[derive(Accounts)]
+pub struct MigrateLpProvider<'info> {
+    #[account(
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// CHECK: A `LiquidityProvider` created before the cost basis fields were added, which is
+    /// too short to deserialize. The seeds pin it to the owner's position and the handler
+    /// checks the program owner and discriminator.
+    #[account(
+        mut,
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub lp_provider: UncheckedAccount<'info>,
+
+    /// Position owner; pays the rent for the extra space
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a pre-upgrade `LiquidityProvider` to `LiquidityProvider::LEN` so it can be loaded
+/// again. The cost basis is seeded from the recorded initial deposit and the fee growth
+/// snapshot starts at the pool's current index, so fees are tracked from the migration onwards.
+pub fn handler(ctx: Context<MigrateLpProvider>) -> Result<()> {
+    let lp_provider = ctx.accounts.lp_provider.to_account_info();
+
+    require!(
+        lp_provider.owner == &crate::ID,
+        AmmError::InvalidPoolConfig
+    );
+    require!(
+        lp_provider.try_borrow_data()?.starts_with(LiquidityProvider::DISCRIMINATOR),
+        AmmError::InvalidPoolConfig
+    );
+
+    if lp_provider.data_len() >= LiquidityProvider::LEN {
+        msg!("LP provider already migrated");
+        return Ok(());
+    }
+
+    // Top up rent for the new size
+    let rent_due = Rent::get()?
+        .minimum_balance(LiquidityProvider::LEN)
+        .saturating_sub(lp_provider.lamports());
+    if rent_due > 0 {
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: lp_provider.clone(),
+            },
+        );
+        system_program::transfer(transfer_ctx, rent_due)?;
+    }
+
+    // New fields are zero-extended
+    lp_provider.resize(LiquidityProvider::LEN)?;
+
+    let mut position = LiquidityProvider::try_deserialize(&mut &lp_provider.try_borrow_data()?[..])?;
+    position.seed_migrated_fields(ctx.accounts.pool.fee_growth_index()?);
+    position.try_serialize(&mut &mut lp_provider.try_borrow_mut_data()?[..])?;
+
+    msg!("LP provider migrated successfully");
+    msg!(
+        "Cost basis: {} A, {} B",
+        position.total_deposited_a,
+        position.total_deposited_b
+    );
+    msg!("Fee growth snapshot: {}", position.fee_growth_snapshot);
+
+    Ok(())
+}
@@ -3,15 +3,56 @@ pub mod admin;
 pub mod farming;
 pub mod flash_loan;
 pub mod initialize_pool;
+pub mod liquidity_staking;
+pub mod migrate_lp_provider;
 pub mod multi_hop;
+pub mod quote;
+pub mod reconcile;
 pub mod remove_liquidity;
+pub mod split_swap;
 pub mod swap;
+pub mod zap;
 
-pub use add_liquidity::*;
+pub use add_liquidity::AddLiquidity;
 pub use admin::*;
 pub use farming::*;
-pub use flash_loan::*;
-pub use initialize_pool::*;
-pub use multi_hop::*;
-pub use remove_liquidity::*;
-pub use swap::*;
+pub use flash_loan::{FlashLoan, FlashLoanRepay};
+pub use initialize_pool::InitializePool;
+pub use liquidity_staking::*;
+pub use migrate_lp_provider::MigrateLpProvider;
+pub use multi_hop::{MultiHopSwap, SwapHop, HOP_ACCOUNTS};
+pub use quote::*;
+pub use reconcile::*;
+pub use remove_liquidity::RemoveLiquidity;
+pub use split_swap::SplitSwap;
+pub use swap::Swap;
+pub use zap::*;
+
+// Several modules name their entry point `handler`, so they are re-exported item by item.
+// `#[program]` also needs the client account modules `#[derive(Accounts)]` generates,
+// and the CPI ones when the `cpi` feature is on.
+pub(crate) use add_liquidity::__client_accounts_add_liquidity;
+pub(crate) use flash_loan::{__client_accounts_flash_loan, __client_accounts_flash_loan_repay};
+pub(crate) use initialize_pool::__client_accounts_initialize_pool;
+pub(crate) use migrate_lp_provider::__client_accounts_migrate_lp_provider;
+pub(crate) use multi_hop::__client_accounts_multi_hop_swap;
+pub(crate) use remove_liquidity::__client_accounts_remove_liquidity;
+pub(crate) use split_swap::__client_accounts_split_swap;
+pub(crate) use swap::__client_accounts_swap;
+
+#[cfg(feature = "cpi")]
+pub(crate) use add_liquidity::__cpi_client_accounts_add_liquidity;
+#[cfg(feature = "cpi")]
+pub(crate) use flash_loan::{__cpi_client_accounts_flash_loan, __cpi_client_accounts_flash_loan_repay};
+#[cfg(feature = "cpi")]
+pub(crate) use initialize_pool::__cpi_client_accounts_initialize_pool;
+#[cfg(feature = "cpi")]
+pub(crate) use migrate_lp_provider::__cpi_client_accounts_migrate_lp_provider;
+#[cfg(feature = "cpi")]
+pub(crate) use multi_hop::__cpi_client_accounts_multi_hop_swap;
+#[cfg(feature = "cpi")]
+pub(crate) use remove_liquidity::__cpi_client_accounts_remove_liquidity;
+#[cfg(feature = "cpi")]
+pub(crate) use split_swap::__cpi_client_accounts_split_swap;
+#[cfg(feature = "cpi")]
+pub(crate) use swap::__cpi_client_accounts_swap;
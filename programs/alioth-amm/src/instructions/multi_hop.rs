@@ -2,53 +2,35 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
+use crate::instructions::swap::check_deadline;
 use crate::state::Pool;
 use crate::utils::{AmmMath, OracleHelper};
 
-/// Multi-hop swap through up to 3 pools
+/// Number of remaining accounts supplied for each hop:
+/// [pool, pool_vault_in, pool_vault_out, oracle_a, oracle_b]
+pub const HOP_ACCOUNTS: usize = 5;
+
+/// A single hop of a multi-hop route
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SwapHop {
+    /// Swap direction through this hop's pool
+    pub is_a_to_b: bool,
+
+    /// Minimum output accepted from this hop
+    pub minimum_amount_out: u64,
+}
+
+/// Multi-hop swap through up to `MAX_SWAP_HOPS` pools
 /// Example: Token A -> Token B -> Token C -> Token D
+///
+/// Remaining accounts hold `HOP_ACCOUNTS` accounts per hop, in route order. For routed swaps
+/// they are followed by one user-owned intermediate token account per hop boundary
+/// (`route.len() - 1` in total); direct swaps pass none.
 #[derive(Accounts)]
-#[instruction(hops: u8)]
 pub struct MultiHopSwap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    // Pool 1 (required)
-    #[account(
-        mut,
-        seeds = [
-            POOL_SEED,
-            pool_1.token_a_mint.as_ref(),
-            pool_1.token_b_mint.as_ref(),
-        ],
-        bump = pool_1.bump,
-    )]
-    pub pool_1: Account<'info, Pool>,
-
-    // Pool 2 (optional, required if hops >= 2)
-    #[account(
-        mut,
-        seeds = [
-            POOL_SEED,
-            pool_2.token_a_mint.as_ref(),
-            pool_2.token_b_mint.as_ref(),
-        ],
-        bump = pool_2.bump,
-    )]
-    pub pool_2: Option<Account<'info, Pool>>,
-
-    // Pool 3 (optional, required if hops == 3)
-    #[account(
-        mut,
-        seeds = [
-            POOL_SEED,
-            pool_3.token_a_mint.as_ref(),
-            pool_3.token_b_mint.as_ref(),
-        ],
-        bump = pool_3.bump,
-    )]
-    pub pool_3: Option<Account<'info, Pool>>,
-
     // User's initial input token account
     #[account(
         mut,
@@ -63,366 +45,496 @@ pub struct MultiHopSwap<'info> {
     )]
     pub user_token_out: Account<'info, TokenAccount>,
 
-    // Intermediate token account 1 (for user, between hop 1 and 2)
-    #[account(
-        mut,
-        constraint = intermediate_token_1.owner == user.key() @ AmmError::InvalidAuthority,
-    )]
-    pub intermediate_token_1: Option<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
 
-    // Intermediate token account 2 (for user, between hop 2 and 3)
-    #[account(
-        mut,
-        constraint = intermediate_token_2.owner == user.key() @ AmmError::InvalidAuthority,
-    )]
-    pub intermediate_token_2: Option<Account<'info, TokenAccount>>,
+/// Multi-hop swap routing each hop's output through the user's intermediate token accounts
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    route: Vec<SwapHop>,
+    deadline: Option<i64>,
+    max_price_impact_bps: Option<u64>,
+) -> Result<()> {
+    check_deadline(deadline, Clock::get()?.unix_timestamp)?;
 
-    // Pool 1 vaults
-    #[account(mut)]
-    pub pool_1_vault_in: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub pool_1_vault_out: Account<'info, TokenAccount>,
+    process_route(ctx, amount_in, minimum_amount_out, route, false, None, max_price_impact_bps)
+}
 
-    // Pool 2 vaults (if applicable)
-    #[account(mut)]
-    pub pool_2_vault_in: Option<Account<'info, TokenAccount>>,
-    
-    #[account(mut)]
-    pub pool_2_vault_out: Option<Account<'info, TokenAccount>>,
+/// Multi-hop swap sending each hop's output straight into the next pool's input vault,
+/// so the user only touches the first input and final output accounts
+pub fn direct_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    route: Vec<SwapHop>,
+) -> Result<()> {
+    process_route(ctx, amount_in, minimum_amount_out, route, true, None, None)
+}
 
-    // Pool 3 vaults (if applicable)
-    #[account(mut)]
-    pub pool_3_vault_in: Option<Account<'info, TokenAccount>>,
-    
-    #[account(mut)]
-    pub pool_3_vault_out: Option<Account<'info, TokenAccount>>,
-
-    // Oracle accounts for each pool
-    /// CHECK: Validated in handler
-    pub oracle_1_a: AccountInfo<'info>,
-    
-    /// CHECK: Validated in handler
-    pub oracle_1_b: AccountInfo<'info>,
-    
-    /// CHECK: Validated in handler
-    pub oracle_2_a: Option<AccountInfo<'info>>,
-    
-    /// CHECK: Validated in handler
-    pub oracle_2_b: Option<AccountInfo<'info>>,
-    
-    /// CHECK: Validated in handler
-    pub oracle_3_a: Option<AccountInfo<'info>>,
-    
-    /// CHECK: Validated in handler
-    pub oracle_3_b: Option<AccountInfo<'info>>,
+/// Multi-hop swap delivering an exact final output. The route is walked backwards to find
+/// each hop's input, then executed forwards with every hop paying out exactly what the next
+/// hop consumes. `direct` selects vault-to-vault routing as in `multi_hop_swap_direct`.
+pub fn exact_out_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+    amount_out: u64,
+    maximum_amount_in: u64,
+    route: Vec<SwapHop>,
+    direct: bool,
+) -> Result<()> {
+    require!(amount_out > 0, AmmError::ZeroAmount);
 
-    pub token_program: Program<'info, Token>,
+    let (hop_accounts, _) = split_route_accounts(ctx.remaining_accounts, route.len(), direct)?;
+
+    // Snapshot each hop's pool to price the route
+    let pools = route
+        .iter()
+        .enumerate()
+        .map(|(index, hop)| {
+            let pool = Account::<Pool>::try_from(&hop_accounts[index * HOP_ACCOUNTS])?;
+            Ok(((*pool).clone(), hop.is_a_to_b))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let amounts_in = route_amounts_in(&pools, amount_out)?;
+
+    // Check slippage tolerance on the total input
+    require!(amounts_in[0] <= maximum_amount_in, AmmError::SlippageExceeded);
+
+    // Each hop must pay out exactly the next hop's input, and the last hop `amount_out`
+    let amounts_out = amounts_in[1..]
+        .iter()
+        .copied()
+        .chain(std::iter::once(amount_out))
+        .collect();
+
+    process_route(ctx, amounts_in[0], amount_out, route, direct, Some(amounts_out), None)
+}
+
+/// Split remaining accounts into hop accounts and intermediate token accounts
+fn split_route_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    hops: usize,
+    direct: bool,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    // Validate hops
+    require!(
+        (1..=MAX_SWAP_HOPS as usize).contains(&hops),
+        AmmError::MaxHopsExceeded
+    );
+
+    let intermediate_count = if direct { 0 } else { hops - 1 };
+    require!(
+        remaining_accounts.len() == hops * HOP_ACCOUNTS + intermediate_count,
+        AmmError::InvalidSwapRoute
+    );
+
+    Ok(remaining_accounts.split_at(hops * HOP_ACCOUNTS))
 }
 
-pub fn handler(
-    ctx: Context<MultiHopSwap>,
+/// Compute the input each hop needs so that the route ends with `amount_out`
+fn route_amounts_in(pools: &[(Pool, bool)], amount_out: u64) -> Result<Vec<u64>> {
+    let mut amounts_in = vec![0u64; pools.len()];
+    let mut required_out = amount_out;
+
+    for (index, (pool, is_a_to_b)) in pools.iter().enumerate().rev() {
+        let (reserve_in, reserve_out) = pool.get_reserves(*is_a_to_b);
+        required_out = AmmMath::get_amount_in(
+            required_out,
+            reserve_in,
+            reserve_out,
+            pool.fee_numerator,
+            pool.fee_denominator,
+        )?;
+        amounts_in[index] = required_out;
+    }
+
+    Ok(amounts_in)
+}
+
+fn process_route<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
     amount_in: u64,
     minimum_amount_out: u64,
-    hops: u8,
+    route: Vec<SwapHop>,
+    direct: bool,
+    exact_amounts_out: Option<Vec<u64>>,
+    max_price_impact_bps: Option<u64>,
 ) -> Result<()> {
-    // Validate hops
-    require!(hops >= 1 && hops <= MAX_SWAP_HOPS, AmmError::MaxHopsExceeded);
+    let hops = route.len();
+    let (hop_accounts, intermediate_accounts) =
+        split_route_accounts(ctx.remaining_accounts, hops, direct)?;
+
+    // Validate initial amount
+    require!(amount_in > 0, AmmError::ZeroAmount);
 
     let clock = Clock::get()?;
     let mut current_amount = amount_in;
+    // `None` once the input already sits in the pool's vault from the previous hop
+    let mut source = Some(ctx.accounts.user_token_in.to_account_info());
+    let mut source_mint = ctx.accounts.user_token_in.mint;
+
+    for (index, hop) in route.iter().enumerate() {
+        // Route this hop's output to the next pool's input vault, the next intermediate
+        // account, or to the user at the end
+        let (destination, destination_mint) = if index + 1 < hops && direct {
+            let next_vault_in = &hop_accounts[(index + 1) * HOP_ACCOUNTS + 1];
+            let next_vault_token = Account::<TokenAccount>::try_from(next_vault_in)?;
+            (next_vault_in.clone(), next_vault_token.mint)
+        } else if index + 1 < hops {
+            let intermediate = &intermediate_accounts[index];
+            let intermediate_token = Account::<TokenAccount>::try_from(intermediate)?;
+            require!(
+                intermediate_token.owner == ctx.accounts.user.key(),
+                AmmError::InvalidAuthority
+            );
+            (intermediate.clone(), intermediate_token.mint)
+        } else {
+            (
+                ctx.accounts.user_token_out.to_account_info(),
+                ctx.accounts.user_token_out.mint,
+            )
+        };
 
-    // Validate initial amount
-    require!(amount_in > 0, AmmError::ZeroAmount);
+        let amount_out = execute_hop(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user,
+            source.as_ref(),
+            &source_mint,
+            &destination,
+            &destination_mint,
+            &hop_accounts[index * HOP_ACCOUNTS..(index + 1) * HOP_ACCOUNTS],
+            current_amount,
+            exact_amounts_out.as_ref().map(|amounts| amounts[index]),
+            hop.is_a_to_b,
+            max_price_impact_bps,
+            clock.unix_timestamp,
+        )?;
 
-    // ========== HOP 1 ==========
-    let pool_1 = &mut ctx.accounts.pool_1;
-    require!(!pool_1.is_paused, AmmError::PoolPaused);
+        // Check intermediate slippage for this hop
+        require!(amount_out >= hop.minimum_amount_out, AmmError::SlippageExceeded);
 
-    // Determine swap direction for hop 1
-    let is_a_to_b_1 = ctx.accounts.user_token_in.mint == pool_1.token_a_mint;
-    require!(
-        is_a_to_b_1 && ctx.accounts.pool_1_vault_in.mint == pool_1.token_a_mint ||
-        !is_a_to_b_1 && ctx.accounts.pool_1_vault_in.mint == pool_1.token_b_mint,
-        AmmError::InvalidSwapRoute
-    );
+        current_amount = amount_out;
+        source = if direct { None } else { Some(destination) };
+        source_mint = destination_mint;
+    }
 
-    // Calculate output from hop 1
-    let (reserve_in_1, reserve_out_1) = if is_a_to_b_1 {
-        (pool_1.reserve_a, pool_1.reserve_b)
-    } else {
-        (pool_1.reserve_b, pool_1.reserve_a)
+    // Final slippage check
+    require!(current_amount >= minimum_amount_out, AmmError::SlippageExceeded);
+
+    msg!("Multi-hop swap completed successfully");
+    msg!("Input amount: {}", amount_in);
+    msg!("Output amount: {}", current_amount);
+    msg!("Hops: {}", hops);
+    msg!("Direct: {}", direct);
+
+    Ok(())
+}
+
+/// Execute a single hop of the route and return its output amount.
+/// With `exact_amount_out` set, the hop pays out exactly that amount as long as
+/// `amount_in` covers it at current reserves.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn execute_hop<'info>(
+    token_program: &Program<'info, Token>,
+    user: &Signer<'info>,
+    source: Option<&AccountInfo<'info>>,
+    source_mint: &Pubkey,
+    destination: &AccountInfo<'info>,
+    destination_mint: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    amount_in: u64,
+    exact_amount_out: Option<u64>,
+    is_a_to_b: bool,
+    max_price_impact_bps: Option<u64>,
+    current_timestamp: i64,
+) -> Result<u64> {
+    let [pool_info, vault_in_info, vault_out_info, oracle_a, oracle_b] = accounts else {
+        return err!(AmmError::InvalidSwapRoute);
     };
 
-    let amount_out_1 = AmmMath::get_amount_out(
-        current_amount,
-        reserve_in_1,
-        reserve_out_1,
-        pool_1.fee_numerator,
-        pool_1.fee_denominator,
+    let mut pool = Account::<Pool>::try_from(pool_info)?;
+    require!(pool_info.is_writable, AmmError::InvalidSwapRoute);
+    pool.require_not_paused(PAUSE_SWAPS)?;
+
+    // Bind every hop account to the pool it claims to belong to
+    validate_hop_accounts(
+        &pool,
+        is_a_to_b,
+        source_mint,
+        destination_mint,
+        &[
+            vault_in_info.key(),
+            vault_out_info.key(),
+            oracle_a.key(),
+            oracle_b.key(),
+        ],
     )?;
 
+    // Calculate output from this hop
+    let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+
+    let amount_out = match exact_amount_out {
+        Some(amount_out) => {
+            let required_in = AmmMath::get_amount_in(
+                amount_out,
+                reserve_in,
+                reserve_out,
+                pool.fee_numerator,
+                pool.fee_denominator,
+            )?;
+            require!(required_in <= amount_in, AmmError::SlippageExceeded);
+            amount_out
+        }
+        None => AmmMath::get_amount_out(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            pool.fee_numerator,
+            pool.fee_denominator,
+        )?,
+    };
+    require!(amount_out > 0, AmmError::ZeroAmount);
+
     // Validate with oracle
     OracleHelper::validate_swap_price(
-        current_amount,
-        amount_out_1,
-        &ctx.accounts.oracle_1_a,
-        &ctx.accounts.oracle_1_b,
-        pool_1.oracle_max_age,
-        pool_1.oracle_max_deviation_bps,
-        is_a_to_b_1,
+        amount_in,
+        amount_out,
+        oracle_a,
+        oracle_b,
+        pool.oracle_max_age,
+        pool.oracle_max_deviation_bps,
+        is_a_to_b,
     )?;
 
-    // Execute hop 1
-    // Transfer from user to pool 1
-    let transfer_1_in_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.user_token_in.to_account_info(),
-            to: ctx.accounts.pool_1_vault_in.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        },
-    );
-    token::transfer(transfer_1_in_ctx, current_amount)?;
+    // Transfer from the user's source account to the pool, unless the previous hop
+    // already delivered the input into this pool's vault
+    if let Some(source) = source {
+        let transfer_in_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: source.clone(),
+                to: vault_in_info.clone(),
+                authority: user.to_account_info(),
+            },
+        );
+        token::transfer(transfer_in_ctx, amount_in)?;
+    }
 
-    // Transfer from pool 1 to intermediate or final destination
-    let seeds_1 = &[
+    // Transfer from the pool to the next vault, intermediate or final destination
+    let seeds = &[
         POOL_SEED,
-        pool_1.token_a_mint.as_ref(),
-        pool_1.token_b_mint.as_ref(),
-        &[pool_1.bump],
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
     ];
-    let signer_1 = &[&seeds_1[..]];
-
-    let destination_1 = if hops > 1 {
-        ctx.accounts.intermediate_token_1.as_ref()
-            .ok_or(AmmError::InvalidSwapRoute)?
-            .to_account_info()
-    } else {
-        ctx.accounts.user_token_out.to_account_info()
-    };
+    let signer = &[&seeds[..]];
 
-    let transfer_1_out_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+    let transfer_out_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.pool_1_vault_out.to_account_info(),
-            to: destination_1,
-            authority: pool_1.to_account_info(),
+            from: vault_out_info.clone(),
+            to: destination.clone(),
+            authority: pool_info.clone(),
         },
-        signer_1,
+        signer,
     );
-    token::transfer(transfer_1_out_ctx, amount_out_1)?;
-
-    // Update pool 1 state
-    let fee_1 = current_amount
-        .checked_mul(pool_1.fee_numerator)
-        .unwrap()
-        .checked_div(pool_1.fee_denominator)
-        .unwrap();
-
-    if is_a_to_b_1 {
-        pool_1.reserve_a = pool_1.reserve_a.checked_add(current_amount).unwrap();
-        pool_1.reserve_b = pool_1.reserve_b.checked_sub(amount_out_1).unwrap();
-        pool_1.total_volume_a = pool_1.total_volume_a.checked_add(current_amount).unwrap();
-        pool_1.total_fees_a = pool_1.total_fees_a.checked_add(fee_1).unwrap();
-    } else {
-        pool_1.reserve_b = pool_1.reserve_b.checked_add(current_amount).unwrap();
-        pool_1.reserve_a = pool_1.reserve_a.checked_sub(amount_out_1).unwrap();
-        pool_1.total_volume_b = pool_1.total_volume_b.checked_add(current_amount).unwrap();
-        pool_1.total_fees_b = pool_1.total_fees_b.checked_add(fee_1).unwrap();
-    }
-    pool_1.update_twap(clock.unix_timestamp)?;
+    token::transfer(transfer_out_ctx, amount_out)?;
 
-    current_amount = amount_out_1;
+    // Update pool state and persist it, since remaining accounts are not serialized by Anchor
+    let spot_price_before = pool.get_spot_price()?;
+    pool.record_swap(amount_in, amount_out, is_a_to_b)?;
+    pool.check_price_impact(spot_price_before, max_price_impact_bps)?;
+    pool.update_twap(current_timestamp)?;
+    pool.exit(&crate::ID)?;
 
-    // ========== HOP 2 (if applicable) ==========
-    if hops >= 2 {
-        let pool_2 = ctx.accounts.pool_2.as_mut()
-            .ok_or(AmmError::InvalidSwapRoute)?;
-        require!(!pool_2.is_paused, AmmError::PoolPaused);
+    Ok(amount_out)
+}
 
-        let is_a_to_b_2 = ctx.accounts.intermediate_token_1.as_ref().unwrap().mint == pool_2.token_a_mint;
-        
-        let (reserve_in_2, reserve_out_2) = if is_a_to_b_2 {
-            (pool_2.reserve_a, pool_2.reserve_b)
-        } else {
-            (pool_2.reserve_b, pool_2.reserve_a)
-        };
+/// Check a hop's mints, vaults and oracles against the pool state.
+/// `hop_keys` holds [pool_vault_in, pool_vault_out, oracle_a, oracle_b].
+fn validate_hop_accounts(
+    pool: &Pool,
+    is_a_to_b: bool,
+    source_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    hop_keys: &[Pubkey; 4],
+) -> Result<()> {
+    let [vault_in, vault_out, oracle_a, oracle_b] = hop_keys;
 
-        let amount_out_2 = AmmMath::get_amount_out(
-            current_amount,
-            reserve_in_2,
-            reserve_out_2,
-            pool_2.fee_numerator,
-            pool_2.fee_denominator,
-        )?;
+    let (input_mint, output_mint, input_vault, output_vault) = if is_a_to_b {
+        (pool.token_a_mint, pool.token_b_mint, pool.token_a_vault, pool.token_b_vault)
+    } else {
+        (pool.token_b_mint, pool.token_a_mint, pool.token_b_vault, pool.token_a_vault)
+    };
 
-        // Validate with oracle
-        OracleHelper::validate_swap_price(
-            current_amount,
-            amount_out_2,
-            ctx.accounts.oracle_2_a.as_ref().ok_or(AmmError::InvalidOracle)?,
-            ctx.accounts.oracle_2_b.as_ref().ok_or(AmmError::InvalidOracle)?,
-            pool_2.oracle_max_age,
-            pool_2.oracle_max_deviation_bps,
-            is_a_to_b_2,
-        )?;
+    // Source and destination must hold this hop's input and output tokens
+    require!(*source_mint == input_mint, AmmError::InvalidSwapRoute);
+    require!(*destination_mint == output_mint, AmmError::InvalidSwapRoute);
 
-        // Execute hop 2
-        let transfer_2_in_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.intermediate_token_1.as_ref().unwrap().to_account_info(),
-                to: ctx.accounts.pool_2_vault_in.as_ref().ok_or(AmmError::InvalidSwapRoute)?.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
-        );
-        token::transfer(transfer_2_in_ctx, current_amount)?;
+    // Vaults must be the pool's own vaults, in swap direction
+    require!(*vault_in == input_vault, AmmError::InvalidPoolConfig);
+    require!(*vault_out == output_vault, AmmError::InvalidPoolConfig);
 
-        let seeds_2 = &[
-            POOL_SEED,
-            pool_2.token_a_mint.as_ref(),
-            pool_2.token_b_mint.as_ref(),
-            &[pool_2.bump],
-        ];
-        let signer_2 = &[&seeds_2[..]];
+    // Oracles must be the ones configured on the pool
+    require!(*oracle_a == pool.oracle_a, AmmError::InvalidOracle);
+    require!(*oracle_b == pool.oracle_b, AmmError::InvalidOracle);
 
-        let destination_2 = if hops > 2 {
-            ctx.accounts.intermediate_token_2.as_ref()
-                .ok_or(AmmError::InvalidSwapRoute)?
-                .to_account_info()
-        } else {
-            ctx.accounts.user_token_out.to_account_info()
-        };
+    Ok(())
+}
 
-        let transfer_2_out_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.pool_2_vault_out.as_ref().ok_or(AmmError::InvalidSwapRoute)?.to_account_info(),
-                to: destination_2,
-                authority: pool_2.to_account_info(),
-            },
-            signer_2,
-        );
-        token::transfer(transfer_2_out_ctx, amount_out_2)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_pool() -> Pool {
+        Pool {
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            token_a_vault: Pubkey::new_unique(),
+            token_b_vault: Pubkey::new_unique(),
+            oracle_a: Pubkey::new_unique(),
+            oracle_b: Pubkey::new_unique(),
+            ..Default::default()
+        }
+    }
 
-        // Update pool 2 state
-        let fee_2 = current_amount
-            .checked_mul(pool_2.fee_numerator)
-            .unwrap()
-            .checked_div(pool_2.fee_denominator)
-            .unwrap();
+    fn hop_keys(pool: &Pool) -> [Pubkey; 4] {
+        [pool.token_a_vault, pool.token_b_vault, pool.oracle_a, pool.oracle_b]
+    }
 
-        if is_a_to_b_2 {
-            pool_2.reserve_a = pool_2.reserve_a.checked_add(current_amount).unwrap();
-            pool_2.reserve_b = pool_2.reserve_b.checked_sub(amount_out_2).unwrap();
-            pool_2.total_volume_a = pool_2.total_volume_a.checked_add(current_amount).unwrap();
-            pool_2.total_fees_a = pool_2.total_fees_a.checked_add(fee_2).unwrap();
-        } else {
-            pool_2.reserve_b = pool_2.reserve_b.checked_add(current_amount).unwrap();
-            pool_2.reserve_a = pool_2.reserve_a.checked_sub(amount_out_2).unwrap();
-            pool_2.total_volume_b = pool_2.total_volume_b.checked_add(current_amount).unwrap();
-            pool_2.total_fees_b = pool_2.total_fees_b.checked_add(fee_2).unwrap();
+    fn assert_error(result: Result<()>, expected: AmmError) {
+        match result {
+            Err(Error::AnchorError(error)) => {
+                assert_eq!(error.error_code_number, u32::from(expected))
+            }
+            other => panic!("expected {:?}, got {:?}", expected, other),
         }
-        pool_2.update_twap(clock.unix_timestamp)?;
+    }
 
-        current_amount = amount_out_2;
+    fn priced_pool(reserve_a: u64, reserve_b: u64) -> Pool {
+        Pool {
+            reserve_a,
+            reserve_b,
+            fee_numerator: 3,
+            fee_denominator: 1000,
+            ..new_pool()
+        }
     }
 
-    // ========== HOP 3 (if applicable) ==========
-    if hops == 3 {
-        let pool_3 = ctx.accounts.pool_3.as_mut()
-            .ok_or(AmmError::InvalidSwapRoute)?;
-        require!(!pool_3.is_paused, AmmError::PoolPaused);
+    #[test]
+    fn test_route_amounts_in_covers_each_hop() {
+        let pools = vec![
+            (priced_pool(1_000_000, 2_000_000), true),
+            (priced_pool(5_000_000, 3_000_000), false),
+            (priced_pool(800_000, 900_000), true),
+        ];
+        let amount_out = 50_000;
+
+        let amounts_in = route_amounts_in(&pools, amount_out).unwrap();
+        assert_eq!(amounts_in.len(), 3);
+
+        // Feeding each hop its planned input must produce at least the next hop's input
+        for (index, (pool, is_a_to_b)) in pools.iter().enumerate() {
+            let (reserve_in, reserve_out) = pool.get_reserves(*is_a_to_b);
+            let produced = AmmMath::get_amount_out(
+                amounts_in[index],
+                reserve_in,
+                reserve_out,
+                pool.fee_numerator,
+                pool.fee_denominator,
+            )
+            .unwrap();
+            let needed = amounts_in.get(index + 1).copied().unwrap_or(amount_out);
+            assert!(produced >= needed);
+        }
+    }
 
-        let is_a_to_b_3 = ctx.accounts.intermediate_token_2.as_ref().unwrap().mint == pool_3.token_a_mint;
-        
-        let (reserve_in_3, reserve_out_3) = if is_a_to_b_3 {
-            (pool_3.reserve_a, pool_3.reserve_b)
-        } else {
-            (pool_3.reserve_b, pool_3.reserve_a)
-        };
+    #[test]
+    fn test_route_amounts_in_rejects_draining_output() {
+        let pools = vec![
+            (priced_pool(1_000_000, 2_000_000), true),
+            (priced_pool(1_000, 1_000), true),
+        ];
+        assert_error(route_amounts_in(&pools, 1_000).map(|_| ()), AmmError::InsufficientLiquidity);
+    }
 
-        let amount_out_3 = AmmMath::get_amount_out(
-            current_amount,
-            reserve_in_3,
-            reserve_out_3,
-            pool_3.fee_numerator,
-            pool_3.fee_denominator,
-        )?;
+    #[test]
+    fn test_valid_hop_accounts() {
+        let pool = new_pool();
+        let keys = hop_keys(&pool);
+        assert!(validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &keys).is_ok());
 
-        // Validate with oracle
-        OracleHelper::validate_swap_price(
-            current_amount,
-            amount_out_3,
-            ctx.accounts.oracle_3_a.as_ref().ok_or(AmmError::InvalidOracle)?,
-            ctx.accounts.oracle_3_b.as_ref().ok_or(AmmError::InvalidOracle)?,
-            pool_3.oracle_max_age,
-            pool_3.oracle_max_deviation_bps,
-            is_a_to_b_3,
-        )?;
+        let reversed = [pool.token_b_vault, pool.token_a_vault, pool.oracle_a, pool.oracle_b];
+        assert!(validate_hop_accounts(&pool, false, &pool.token_b_mint, &pool.token_a_mint, &reversed).is_ok());
+    }
 
-        // Execute hop 3
-        let transfer_3_in_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.intermediate_token_2.as_ref().unwrap().to_account_info(),
-                to: ctx.accounts.pool_3_vault_in.as_ref().ok_or(AmmError::InvalidSwapRoute)?.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
+    #[test]
+    fn test_rejects_mismatched_mints() {
+        let pool = new_pool();
+        let keys = hop_keys(&pool);
+        let other_mint = Pubkey::new_unique();
+
+        assert_error(
+            validate_hop_accounts(&pool, true, &other_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidSwapRoute,
+        );
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &other_mint, &keys),
+            AmmError::InvalidSwapRoute,
+        );
+        // Direction flag disagreeing with the supplied mints
+        assert_error(
+            validate_hop_accounts(&pool, false, &pool.token_a_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidSwapRoute,
         );
-        token::transfer(transfer_3_in_ctx, current_amount)?;
+    }
 
-        let seeds_3 = &[
-            POOL_SEED,
-            pool_3.token_a_mint.as_ref(),
-            pool_3.token_b_mint.as_ref(),
-            &[pool_3.bump],
-        ];
-        let signer_3 = &[&seeds_3[..]];
+    #[test]
+    fn test_rejects_mismatched_vaults() {
+        let pool = new_pool();
+        let attacker_vault = Pubkey::new_unique();
 
-        let transfer_3_out_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.pool_3_vault_out.as_ref().ok_or(AmmError::InvalidSwapRoute)?.to_account_info(),
-                to: ctx.accounts.user_token_out.to_account_info(),
-                authority: pool_3.to_account_info(),
-            },
-            signer_3,
+        let mut keys = hop_keys(&pool);
+        keys[0] = attacker_vault;
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidPoolConfig,
         );
-        token::transfer(transfer_3_out_ctx, amount_out_3)?;
-
-        // Update pool 3 state
-        let fee_3 = current_amount
-            .checked_mul(pool_3.fee_numerator)
-            .unwrap()
-            .checked_div(pool_3.fee_denominator)
-            .unwrap();
 
-        if is_a_to_b_3 {
-            pool_3.reserve_a = pool_3.reserve_a.checked_add(current_amount).unwrap();
-            pool_3.reserve_b = pool_3.reserve_b.checked_sub(amount_out_3).unwrap();
-            pool_3.total_volume_a = pool_3.total_volume_a.checked_add(current_amount).unwrap();
-            pool_3.total_fees_a = pool_3.total_fees_a.checked_add(fee_3).unwrap();
-        } else {
-            pool_3.reserve_b = pool_3.reserve_b.checked_add(current_amount).unwrap();
-            pool_3.reserve_a = pool_3.reserve_a.checked_sub(amount_out_3).unwrap();
-            pool_3.total_volume_b = pool_3.total_volume_b.checked_add(current_amount).unwrap();
-            pool_3.total_fees_b = pool_3.total_fees_b.checked_add(fee_3).unwrap();
-        }
-        pool_3.update_twap(clock.unix_timestamp)?;
+        let mut keys = hop_keys(&pool);
+        keys[1] = attacker_vault;
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidPoolConfig,
+        );
 
-        current_amount = amount_out_3;
+        // Pool vaults supplied in the wrong order for the direction
+        let swapped = [pool.token_b_vault, pool.token_a_vault, pool.oracle_a, pool.oracle_b];
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &swapped),
+            AmmError::InvalidPoolConfig,
+        );
     }
 
-    // Final slippage check
-    require!(current_amount >= minimum_amount_out, AmmError::SlippageExceeded);
+    #[test]
+    fn test_rejects_mismatched_oracles() {
+        let pool = new_pool();
+        let fake_oracle = Pubkey::new_unique();
 
-    msg!("Multi-hop swap completed successfully");
-    msg!("Input amount: {}", amount_in);
-    msg!("Output amount: {}", current_amount);
-    msg!("Hops: {}", hops);
+        let mut keys = hop_keys(&pool);
+        keys[2] = fake_oracle;
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidOracle,
+        );
 
-    Ok(())
+        let mut keys = hop_keys(&pool);
+        keys[3] = fake_oracle;
+        assert_error(
+            validate_hop_accounts(&pool, true, &pool.token_a_mint, &pool.token_b_mint, &keys),
+            AmmError::InvalidOracle,
+        );
+    }
 }
-
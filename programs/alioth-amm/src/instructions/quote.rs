@@ -0,0 +1,387 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::{LiquidityProvider, Pool};
+use crate::utils::AmmMath;
+
+/// Read-only access to a pool for quoting. Quotes are returned through Anchor return data,
+/// so they can be read from a simulated transaction or from a CPI caller.
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+}
+
+/// Read-only access to an LP position and its pool
+#[derive(Accounts)]
+pub struct QuotePosition<'info> {
+    #[account(
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            lp_provider.owner.as_ref(),
+        ],
+        bump = lp_provider.bump,
+    )]
+    pub lp_provider: Account<'info, LiquidityProvider>,
+}
+
+/// Quote for a swap against live pool state
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct SwapQuote {
+    /// Input amount paid by the trader
+    pub amount_in: u64,
+
+    /// Output amount received by the trader
+    pub amount_out: u64,
+
+    /// Part of the input kept by the pool as fee
+    pub fee_amount: u64,
+
+    /// Spot price of token A in token B before the swap (scaled by 10^9)
+    pub spot_price_before: u64,
+
+    /// Spot price of token A in token B after the swap (scaled by 10^9)
+    pub spot_price_after: u64,
+
+    /// Spot price movement caused by the swap, in basis points
+    pub price_impact_bps: u64,
+}
+
+impl SwapQuote {
+    /// Build a quote by applying the swap to a copy of the pool
+    pub fn new(pool: &Pool, amount_in: u64, amount_out: u64, is_a_to_b: bool) -> Result<Self> {
+        let spot_price_before = pool.get_spot_price()?;
+
+        let mut pool_after = pool.clone();
+        let fee_amount = pool_after.record_swap(amount_in, amount_out, is_a_to_b)?;
+        let spot_price_after = pool_after.get_spot_price()?;
+
+        Ok(Self {
+            amount_in,
+            amount_out,
+            fee_amount,
+            spot_price_before,
+            spot_price_after,
+            price_impact_bps: AmmMath::calculate_price_impact_bps(spot_price_before, spot_price_after)?,
+        })
+    }
+}
+
+/// Quote for adding liquidity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct LiquidityQuote {
+    /// Token A that would be deposited at the current reserve ratio
+    pub amount_a: u64,
+
+    /// Token B that would be deposited at the current reserve ratio
+    pub amount_b: u64,
+
+    /// LP tokens that would be minted
+    pub liquidity: u64,
+}
+
+/// Quote for removing liquidity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct WithdrawQuote {
+    /// Token A that would be withdrawn
+    pub amount_a: u64,
+
+    /// Token B that would be withdrawn
+    pub amount_b: u64,
+}
+
+/// Value and PnL of an LP position at the pool's spot price. Values are in token B units.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct PositionQuote {
+    /// LP tokens recorded for the position
+    pub lp_token_amount: u64,
+
+    /// Token A redeemable for those LP tokens
+    pub amount_a: u64,
+
+    /// Token B redeemable for those LP tokens
+    pub amount_b: u64,
+
+    /// Current value of the position
+    pub position_value: u128,
+
+    /// Token A earned from swap fees since the LP tokens were minted
+    pub fees_a: u64,
+
+    /// Token B earned from swap fees since the LP tokens were minted
+    pub fees_b: u64,
+
+    /// Value of holding the net deposited tokens (deposits minus withdrawals) instead
+    pub hold_value: i128,
+
+    /// Lifetime profit or loss against holding, fees included
+    pub pnl_vs_hold: i128,
+
+    /// Impermanent loss against holding, fees excluded (zero or negative)
+    pub impermanent_loss: i128,
+}
+
+impl PositionQuote {
+    pub fn new(pool: &Pool, lp_provider: &LiquidityProvider) -> Result<Self> {
+        require!(pool.reserve_a > 0 && pool.reserve_b > 0, AmmError::InsufficientLiquidity);
+
+        let lp_token_amount = lp_provider.lp_token_amount;
+        let (amount_a, amount_b) = if lp_token_amount > 0 {
+            AmmMath::calculate_withdraw_amounts(
+                lp_token_amount,
+                pool.total_lp_supply,
+                pool.reserve_a,
+                pool.reserve_b,
+            )?
+        } else {
+            (0, 0)
+        };
+
+        // The share of the redeemable amounts that came from fee growth since minting
+        let fee_growth_index = pool.fee_growth_index()?;
+        let fee_share = |amount: u64| -> Result<u64> {
+            if fee_growth_index <= lp_provider.fee_growth_snapshot {
+                return Ok(0);
+            }
+            Ok(((amount as u128)
+                .checked_mul(fee_growth_index - lp_provider.fee_growth_snapshot)
+                .ok_or(AmmError::MathOverflow)?
+                / fee_growth_index) as u64)
+        };
+        let fees_a = fee_share(amount_a)?;
+        let fees_b = fee_share(amount_b)?;
+
+        // Value token A at the spot price: amount_a * reserve_b / reserve_a
+        let value = |amount_a: i128, amount_b: i128| -> Result<i128> {
+            amount_a
+                .checked_mul(pool.reserve_b as i128)
+                .map(|value_a| value_a / pool.reserve_a as i128)
+                .and_then(|value_a| value_a.checked_add(amount_b))
+                .ok_or(AmmError::MathOverflow.into())
+        };
+
+        let position_value = value(amount_a as i128, amount_b as i128)?;
+        let fees_value = value(fees_a as i128, fees_b as i128)?;
+        let hold_value = value(
+            lp_provider.total_deposited_a as i128 - lp_provider.total_withdrawn_a as i128,
+            lp_provider.total_deposited_b as i128 - lp_provider.total_withdrawn_b as i128,
+        )?;
+
+        let pnl_vs_hold = position_value - hold_value;
+
+        Ok(Self {
+            lp_token_amount,
+            amount_a,
+            amount_b,
+            position_value: position_value as u128,
+            fees_a,
+            fees_b,
+            hold_value,
+            pnl_vs_hold,
+            impermanent_loss: (pnl_vs_hold - fees_value).min(0),
+        })
+    }
+}
+
+pub fn quote_swap_handler(ctx: Context<Quote>, amount_in: u64, is_a_to_b: bool) -> Result<SwapQuote> {
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused(PAUSE_SWAPS)?;
+    require!(amount_in > 0, AmmError::ZeroAmount);
+
+    let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+    let amount_out = AmmMath::get_amount_out(
+        amount_in,
+        reserve_in,
+        reserve_out,
+        pool.fee_numerator,
+        pool.fee_denominator,
+    )?;
+
+    SwapQuote::new(pool, amount_in, amount_out, is_a_to_b)
+}
+
+pub fn quote_swap_exact_out_handler(
+    ctx: Context<Quote>,
+    amount_out: u64,
+    is_a_to_b: bool,
+) -> Result<SwapQuote> {
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused(PAUSE_SWAPS)?;
+    require!(amount_out > 0, AmmError::ZeroAmount);
+
+    let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+    let amount_in = AmmMath::get_amount_in(
+        amount_out,
+        reserve_in,
+        reserve_out,
+        pool.fee_numerator,
+        pool.fee_denominator,
+    )?;
+
+    SwapQuote::new(pool, amount_in, amount_out, is_a_to_b)
+}
+
+pub fn quote_add_liquidity_handler(
+    ctx: Context<Quote>,
+    amount_a_desired: u64,
+    amount_b_desired: u64,
+) -> Result<LiquidityQuote> {
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused(PAUSE_DEPOSITS)?;
+    require!(amount_a_desired > 0 && amount_b_desired > 0, AmmError::ZeroAmount);
+
+    let (amount_a, amount_b) = AmmMath::calculate_optimal_deposit(
+        amount_a_desired,
+        amount_b_desired,
+        0,
+        0,
+        pool.reserve_a,
+        pool.reserve_b,
+    )?;
+
+    Ok(LiquidityQuote {
+        amount_a,
+        amount_b,
+        liquidity: pool.calculate_liquidity_to_mint(amount_a, amount_b)?,
+    })
+}
+
+pub fn quote_remove_liquidity_handler(
+    ctx: Context<Quote>,
+    liquidity_amount: u64,
+) -> Result<WithdrawQuote> {
+    let pool = &ctx.accounts.pool;
+    pool.require_not_paused(PAUSE_WITHDRAWALS)?;
+    require!(liquidity_amount > 0, AmmError::ZeroAmount);
+
+    let (amount_a, amount_b) = AmmMath::calculate_withdraw_amounts(
+        liquidity_amount,
+        pool.total_lp_supply,
+        pool.reserve_a,
+        pool.reserve_b,
+    )?;
+
+    Ok(WithdrawQuote { amount_a, amount_b })
+}
+
+pub fn quote_position_handler(ctx: Context<QuotePosition>) -> Result<PositionQuote> {
+    PositionQuote::new(&ctx.accounts.pool, &ctx.accounts.lp_provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_quote_matches_swap_accounting() {
+        let pool = Pool {
+            reserve_a: 1_000_000,
+            reserve_b: 2_000_000,
+            fee_numerator: 3,
+            fee_denominator: 1000,
+            ..Default::default()
+        };
+
+        let quote = SwapQuote::new(&pool, 10_000, 19_743, true).unwrap();
+        assert_eq!(quote.fee_amount, 30);
+        assert_eq!(quote.spot_price_before, 2_000_000_000);
+        assert_eq!(quote.spot_price_after, 1_960_650_495);
+        assert_eq!(quote.price_impact_bps, 196);
+
+        // Quoting leaves the pool untouched
+        assert_eq!(pool.reserve_a, 1_000_000);
+        assert_eq!(pool.total_fees_a, 0);
+    }
+
+    fn swap(pool: &mut Pool, amount_in: u64, is_a_to_b: bool) {
+        let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+        let amount_out = AmmMath::get_amount_out(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            pool.fee_numerator,
+            pool.fee_denominator,
+        )
+        .unwrap();
+        pool.record_swap(amount_in, amount_out, is_a_to_b).unwrap();
+    }
+
+    fn funded_position() -> (Pool, LiquidityProvider) {
+        let mut pool = Pool {
+            fee_numerator: 3,
+            fee_denominator: 1000,
+            ..Default::default()
+        };
+        let mut lp_provider = LiquidityProvider::default();
+
+        let (liquidity, _) = pool.record_deposit(1_000_000_000, 1_000_000_000).unwrap();
+        lp_provider
+            .record_deposit(liquidity, 1_000_000_000, 1_000_000_000, pool.fee_growth_index().unwrap())
+            .unwrap();
+
+        (pool, lp_provider)
+    }
+
+    #[test]
+    fn test_position_quote_fees_without_price_change() {
+        let (mut pool, lp_provider) = funded_position();
+
+        // Round trips leave the price roughly where it was but accrue fees
+        for _ in 0..10 {
+            swap(&mut pool, 100_000_000, true);
+            let reserve_a = pool.reserve_a;
+            swap(&mut pool, 100_000_000, false);
+            // Bring token A back to its starting reserve
+            let excess = pool.reserve_a.saturating_sub(reserve_a);
+            if excess > 0 {
+                swap(&mut pool, excess / 2, true);
+            }
+        }
+
+        let quote = PositionQuote::new(&pool, &lp_provider).unwrap();
+        assert!(quote.fees_a > 0 && quote.fees_b > 0);
+        assert_eq!(quote.pnl_vs_hold, quote.position_value as i128 - quote.hold_value);
+        assert!(quote.impermanent_loss <= 0);
+        assert!(quote.pnl_vs_hold > quote.impermanent_loss);
+    }
+
+    #[test]
+    fn test_position_quote_impermanent_loss_after_price_move() {
+        let (mut pool, mut lp_provider) = funded_position();
+        swap(&mut pool, 500_000_000, true);
+
+        let quote = PositionQuote::new(&pool, &lp_provider).unwrap();
+        assert!(quote.impermanent_loss < 0);
+        assert!(quote.position_value > 0);
+        assert_eq!(quote.pnl_vs_hold, quote.position_value as i128 - quote.hold_value);
+
+        // Withdrawing everything realises the position: nothing is left and the PnL is what
+        // was taken out beyond the net tokens deposited
+        let liquidity = lp_provider.lp_token_amount;
+        let (amount_a, amount_b) = pool.record_withdrawal(liquidity).unwrap();
+        lp_provider.record_withdrawal(liquidity, amount_a, amount_b);
+
+        let closed = PositionQuote::new(&pool, &lp_provider).unwrap();
+        assert_eq!(closed.position_value, 0);
+        assert_eq!(closed.pnl_vs_hold, -closed.hold_value);
+        assert_eq!(closed.fees_a + closed.fees_b, 0);
+    }
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::events::{PoolSkimmed, ReservesSynced};
+use crate::state::Pool;
+
+// ========== Skim ==========
+
+#[derive(Accounts)]
+pub struct SkimReserves<'info> {
+    #[account(
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    /// Receives the token A surplus; any account for the mint, e.g. a protocol treasury
+    #[account(
+        mut,
+        constraint = recipient_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub recipient_token_a: Account<'info, TokenAccount>,
+
+    /// Receives the token B surplus; any account for the mint, e.g. a protocol treasury
+    #[account(
+        mut,
+        constraint = recipient_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
+    )]
+    pub recipient_token_b: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/// Permissionless: send vault balances above the tracked reserves to the recipients.
+/// Reserves are untouched, so LPs are never affected.
+pub fn skim_handler(ctx: Context<SkimReserves>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    let (excess_a, excess_b) = pool.excess_balances(
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    );
+    require!(excess_a > 0 || excess_b > 0, AmmError::ZeroAmount);
+
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    for (vault, recipient, amount) in [
+        (&ctx.accounts.token_a_vault, &ctx.accounts.recipient_token_a, excess_a),
+        (&ctx.accounts.token_b_vault, &ctx.accounts.recipient_token_b, excess_b),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: recipient.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    emit!(PoolSkimmed {
+        pool: pool.key(),
+        recipient_token_a: ctx.accounts.recipient_token_a.key(),
+        recipient_token_b: ctx.accounts.recipient_token_b.key(),
+        amount_a: excess_a,
+        amount_b: excess_b,
+    });
+
+    msg!("Pool skimmed successfully");
+    msg!("Amount A: {}, Amount B: {}", excess_a, excess_b);
+
+    Ok(())
+}
+
+// ========== Sync ==========
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ AmmError::Unauthorized,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+}
+
+/// Reset reserves to the actual vault balances (admin only), folding any surplus into the pool
+pub fn sync_handler(ctx: Context<SyncReserves>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let clock = Clock::get()?;
+
+    // Reserves without outstanding LP would belong to nobody
+    require!(pool.total_lp_supply > 0, AmmError::InsufficientLiquidity);
+
+    let new_reserve_a = ctx.accounts.token_a_vault.amount;
+    let new_reserve_b = ctx.accounts.token_b_vault.amount;
+    require!(
+        new_reserve_a > 0 && new_reserve_b > 0,
+        AmmError::InsufficientLiquidity
+    );
+
+    // Accrue TWAP at the old price before it moves
+    pool.update_twap(clock.unix_timestamp)?;
+
+    let old_reserve_a = pool.reserve_a;
+    let old_reserve_b = pool.reserve_b;
+    pool.reserve_a = new_reserve_a;
+    pool.reserve_b = new_reserve_b;
+
+    emit!(ReservesSynced {
+        pool: pool.key(),
+        authority: ctx.accounts.authority.key(),
+        old_reserve_a,
+        old_reserve_b,
+        new_reserve_a,
+        new_reserve_b,
+        delta_a: new_reserve_a as i128 - old_reserve_a as i128,
+        delta_b: new_reserve_b as i128 - old_reserve_b as i128,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Pool reserves synced to vault balances");
+    msg!("Reserve A: {} -> {}", old_reserve_a, new_reserve_a);
+    msg!("Reserve B: {} -> {}", old_reserve_b, new_reserve_b);
+
+    Ok(())
+}
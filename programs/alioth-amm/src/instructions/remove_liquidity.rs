@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Burn, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Burn, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
 use crate::state::{Pool, LiquidityProvider};
-use crate::utils::AmmMath;
+use crate::utils::NativeSolHelper;
 
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
@@ -18,6 +18,7 @@ pub struct RemoveLiquidity<'info> {
     )]
     pub pool: Account<'info, Pool>,
 
+    /// Optional analytics record; redemption only depends on burning LP tokens
     #[account(
         mut,
         seeds = [
@@ -28,24 +29,26 @@ pub struct RemoveLiquidity<'info> {
         bump = lp_provider.bump,
         constraint = lp_provider.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub lp_provider: Account<'info, LiquidityProvider>,
+    pub lp_provider: Option<Account<'info, LiquidityProvider>>,
 
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// May be omitted when token A is wSOL and `native_sol` is set
     #[account(
         mut,
         constraint = user_token_a.mint == pool.token_a_mint @ AmmError::TokenMintMismatch,
         constraint = user_token_a.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_a: Account<'info, TokenAccount>,
+    pub user_token_a: Option<Account<'info, TokenAccount>>,
 
+    /// May be omitted when token B is wSOL and `native_sol` is set
     #[account(
         mut,
         constraint = user_token_b.mint == pool.token_b_mint @ AmmError::TokenMintMismatch,
         constraint = user_token_b.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_b: Account<'info, TokenAccount>,
+    pub user_token_b: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -72,7 +75,25 @@ pub struct RemoveLiquidity<'info> {
     )]
     pub user_lp_token: Account<'info, TokenAccount>,
 
+    /// Temporary wSOL account for `native_sol`, created here and closed before the instruction ends
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            WSOL_TEMP_SEED,
+            user.key().as_ref(),
+        ],
+        bump,
+        token::mint = native_mint,
+        token::authority = user,
+    )]
+    pub wsol_temp: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Account<'info, Mint>>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
@@ -80,27 +101,19 @@ pub fn handler(
     liquidity_amount: u64,
     min_amount_a: u64,
     min_amount_b: u64,
+    native_sol: bool,
 ) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
 
     // Check if pool is paused
-    require!(!pool.is_paused, AmmError::PoolPaused);
+    pool.require_not_paused(PAUSE_WITHDRAWALS)?;
 
     // Validate liquidity amount
     require!(liquidity_amount > 0, AmmError::ZeroAmount);
-    require!(
-        ctx.accounts.lp_provider.lp_token_amount >= liquidity_amount,
-        AmmError::InsufficientLiquidity
-    );
 
-    // Calculate amounts to withdraw
-    let (amount_a, amount_b) = AmmMath::calculate_withdraw_amounts(
-        liquidity_amount,
-        pool.total_lp_supply,
-        pool.reserve_a,
-        pool.reserve_b,
-    )?;
+    // Update pool state and calculate amounts to withdraw
+    let (amount_a, amount_b) = pool.record_withdrawal(liquidity_amount)?;
 
     // Check slippage tolerance
     require!(amount_a >= min_amount_a, AmmError::SlippageExceeded);
@@ -117,6 +130,23 @@ pub fn handler(
     );
     token::burn(burn_ctx, liquidity_amount)?;
 
+    // With native_sol, the wSOL side is paid into the temporary account and unwrapped
+    NativeSolHelper::require_native_pair(native_sol, &pool.token_a_mint, &pool.token_b_mint)?;
+    let destination_a = NativeSolHelper::user_account(
+        native_sol,
+        &pool.token_a_mint,
+        &ctx.accounts.user_token_a,
+        &ctx.accounts.wsol_temp,
+    )?
+    .to_account_info();
+    let destination_b = NativeSolHelper::user_account(
+        native_sol,
+        &pool.token_b_mint,
+        &ctx.accounts.user_token_b,
+        &ctx.accounts.wsol_temp,
+    )?
+    .to_account_info();
+
     // Transfer tokens from pool to user
     let seeds = &[
         POOL_SEED,
@@ -130,7 +160,7 @@ pub fn handler(
         ctx.accounts.token_program.to_account_info(),
         Transfer {
             from: ctx.accounts.token_a_vault.to_account_info(),
-            to: ctx.accounts.user_token_a.to_account_info(),
+            to: destination_a,
             authority: pool.to_account_info(),
         },
         signer,
@@ -141,24 +171,25 @@ pub fn handler(
         ctx.accounts.token_program.to_account_info(),
         Transfer {
             from: ctx.accounts.token_b_vault.to_account_info(),
-            to: ctx.accounts.user_token_b.to_account_info(),
+            to: destination_b,
             authority: pool.to_account_info(),
         },
         signer,
     );
     token::transfer(transfer_b_ctx, amount_b)?;
 
-    // Update pool state
-    pool.reserve_a = pool.reserve_a.checked_sub(amount_a).unwrap();
-    pool.reserve_b = pool.reserve_b.checked_sub(amount_b).unwrap();
-    pool.total_lp_supply = pool.total_lp_supply.checked_sub(liquidity_amount).unwrap();
-
     // Update TWAP
     pool.update_twap(clock.unix_timestamp)?;
 
     // Update LP provider state
-    let lp_provider = &mut ctx.accounts.lp_provider;
-    lp_provider.lp_token_amount = lp_provider.lp_token_amount.checked_sub(liquidity_amount).unwrap();
+    if let Some(lp_provider) = &mut ctx.accounts.lp_provider {
+        lp_provider.record_withdrawal(liquidity_amount, amount_a, amount_b);
+    }
+
+    // Unwrap by closing the temporary wSOL account, sending its balance and rent to the user
+    if let Some(wsol_temp) = &ctx.accounts.wsol_temp {
+        NativeSolHelper::close_temp_account(&ctx.accounts.token_program, wsol_temp, &ctx.accounts.user)?;
+    }
 
     msg!("Liquidity removed successfully");
     msg!("LP tokens burned: {}", liquidity_amount);
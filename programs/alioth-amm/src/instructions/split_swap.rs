@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::instructions::multi_hop::{execute_hop, HOP_ACCOUNTS};
+use crate::state::Pool;
+
+/// Swap one pair across up to `MAX_SPLIT_POOLS` parallel pools. A pair has one pool per mint
+/// order, so each pool's swap direction follows from the input mint.
+///
+/// Remaining accounts hold `HOP_ACCOUNTS` accounts per pool:
+/// [pool, pool_vault_in, pool_vault_out, oracle_a, oracle_b]
+#[derive(Accounts)]
+pub struct SplitSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // User's input token account
+    #[account(
+        mut,
+        constraint = user_token_in.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    // User's output token account
+    #[account(
+        mut,
+        constraint = user_token_out.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SplitSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    weights_bps: Vec<u16>,
+) -> Result<()> {
+    // Validate pool count
+    let pools = weights_bps.len();
+    require!(
+        (1..=MAX_SPLIT_POOLS as usize).contains(&pools),
+        AmmError::InvalidSwapRoute
+    );
+    require!(
+        ctx.remaining_accounts.len() == pools * HOP_ACCOUNTS,
+        AmmError::InvalidSwapRoute
+    );
+
+    // Validate swap amount
+    require!(amount_in > 0, AmmError::ZeroAmount);
+
+    let amounts_in = split_amount(amount_in, &weights_bps)?;
+
+    let clock = Clock::get()?;
+    let user_token_in = ctx.accounts.user_token_in.to_account_info();
+    let user_token_out = ctx.accounts.user_token_out.to_account_info();
+    let mut total_amount_out: u64 = 0;
+
+    for (index, portion) in amounts_in.iter().enumerate() {
+        let hop_accounts = &ctx.remaining_accounts[index * HOP_ACCOUNTS..(index + 1) * HOP_ACCOUNTS];
+        let pool = Account::<Pool>::try_from(&hop_accounts[0])?;
+        let is_a_to_b = pool.token_a_mint == ctx.accounts.user_token_in.mint;
+
+        let amount_out = execute_hop(
+            &ctx.accounts.token_program,
+            &ctx.accounts.user,
+            Some(&user_token_in),
+            &ctx.accounts.user_token_in.mint,
+            &user_token_out,
+            &ctx.accounts.user_token_out.mint,
+            hop_accounts,
+            *portion,
+            None,
+            is_a_to_b,
+            None,
+            clock.unix_timestamp,
+        )?;
+
+        total_amount_out = total_amount_out
+            .checked_add(amount_out)
+            .ok_or(AmmError::MathOverflow)?;
+    }
+
+    // Check slippage tolerance on the aggregated output
+    require!(total_amount_out >= minimum_amount_out, AmmError::SlippageExceeded);
+
+    msg!("Split swap completed successfully");
+    msg!("Input amount: {}", amount_in);
+    msg!("Output amount: {}", total_amount_out);
+    msg!("Pools: {}", pools);
+
+    Ok(())
+}
+
+/// Split `amount_in` by weights in basis points; rounding dust goes to the last pool
+fn split_amount(amount_in: u64, weights_bps: &[u16]) -> Result<Vec<u64>> {
+    let total_weight: u64 = weights_bps.iter().map(|weight| *weight as u64).sum();
+    require!(total_weight == MAX_BPS, AmmError::InvalidSwapRoute);
+
+    let mut amounts = Vec::with_capacity(weights_bps.len());
+    let mut allocated: u64 = 0;
+
+    for (index, weight) in weights_bps.iter().enumerate() {
+        let amount = if index + 1 == weights_bps.len() {
+            amount_in - allocated
+        } else {
+            u64::try_from((amount_in as u128) * (*weight as u128) / (MAX_BPS as u128))
+                .map_err(|_| AmmError::MathOverflow)?
+        };
+
+        // Every listed pool must receive part of the trade
+        require!(amount > 0, AmmError::ZeroAmount);
+
+        allocated = allocated.checked_add(amount).ok_or(AmmError::MathOverflow)?;
+        amounts.push(amount);
+    }
+
+    Ok(amounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_amount_by_weights() {
+        assert_eq!(split_amount(1_000, &[5_000, 5_000]).unwrap(), vec![500, 500]);
+        assert_eq!(split_amount(1_000, &[10_000]).unwrap(), vec![1_000]);
+
+        // Rounding dust lands on the last pool and nothing is lost
+        let amounts = split_amount(1_001, &[3_333, 3_333, 3_334]).unwrap();
+        assert_eq!(amounts, vec![333, 333, 335]);
+        assert_eq!(amounts.iter().sum::<u64>(), 1_001);
+    }
+
+    #[test]
+    fn test_split_amount_rejects_bad_weights() {
+        assert!(split_amount(1_000, &[5_000, 4_000]).is_err());
+        assert!(split_amount(1_000, &[6_000, 6_000]).is_err());
+
+        // A zero weight (or a portion that rounds to zero) would leave a pool unused
+        assert!(split_amount(1_000, &[0, 10_000]).is_err());
+        assert!(split_amount(10, &[1, 9_999]).is_err());
+    }
+}
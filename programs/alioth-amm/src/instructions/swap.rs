@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer};
 use crate::constants::*;
 use crate::errors::AmmError;
 use crate::state::Pool;
-use crate::utils::{AmmMath, OracleHelper};
+use crate::utils::{AmmMath, NativeSolHelper, OracleHelper};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
@@ -21,17 +21,25 @@ pub struct Swap<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// Pays the swap input; may be omitted when `native_sol` funds a wSOL input from lamports
     #[account(
         mut,
         constraint = user_token_in.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_in: Account<'info, TokenAccount>,
+    pub user_token_in: Option<Account<'info, TokenAccount>>,
 
+    /// Receives the swap output; may be omitted when `recipient_token_out` is given or when
+    /// `native_sol` unwraps a wSOL output to lamports
     #[account(
         mut,
         constraint = user_token_out.owner == user.key() @ AmmError::InvalidAuthority,
     )]
-    pub user_token_out: Account<'info, TokenAccount>,
+    pub user_token_out: Option<Account<'info, TokenAccount>>,
+
+    /// Optional output account owned by a third party; receives the swap output instead of
+    /// `user_token_out` when provided
+    #[account(mut)]
+    pub recipient_token_out: Option<Account<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -57,7 +65,25 @@ pub struct Swap<'info> {
     )]
     pub oracle_b: AccountInfo<'info>,
 
+    /// Temporary wSOL account for `native_sol`, created here and closed before the instruction ends
+    #[account(
+        init,
+        payer = user,
+        seeds = [
+            WSOL_TEMP_SEED,
+            user.key().as_ref(),
+        ],
+        bump,
+        token::mint = native_mint,
+        token::authority = user,
+    )]
+    pub wsol_temp: Option<Account<'info, TokenAccount>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Account<'info, Mint>>,
+
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
@@ -65,59 +91,20 @@ pub fn handler(
     amount_in: u64,
     minimum_amount_out: u64,
     is_a_to_b: bool,
+    deadline: Option<i64>,
+    max_price_impact_bps: Option<u64>,
+    native_sol: bool,
 ) -> Result<()> {
-    let pool = &mut ctx.accounts.pool;
-    let clock = Clock::get()?;
-
-    // Check if pool is paused
-    require!(!pool.is_paused, AmmError::PoolPaused);
-
     // Validate swap amount
     require!(amount_in > 0, AmmError::ZeroAmount);
 
-    // Verify token accounts match swap direction
-    if is_a_to_b {
-        require!(
-            ctx.accounts.user_token_in.mint == pool.token_a_mint,
-            AmmError::TokenMintMismatch
-        );
-        require!(
-            ctx.accounts.user_token_out.mint == pool.token_b_mint,
-            AmmError::TokenMintMismatch
-        );
-        require!(
-            ctx.accounts.pool_token_in.key() == pool.token_a_vault,
-            AmmError::InvalidPoolConfig
-        );
-        require!(
-            ctx.accounts.pool_token_out.key() == pool.token_b_vault,
-            AmmError::InvalidPoolConfig
-        );
-    } else {
-        require!(
-            ctx.accounts.user_token_in.mint == pool.token_b_mint,
-            AmmError::TokenMintMismatch
-        );
-        require!(
-            ctx.accounts.user_token_out.mint == pool.token_a_mint,
-            AmmError::TokenMintMismatch
-        );
-        require!(
-            ctx.accounts.pool_token_in.key() == pool.token_b_vault,
-            AmmError::InvalidPoolConfig
-        );
-        require!(
-            ctx.accounts.pool_token_out.key() == pool.token_a_vault,
-            AmmError::InvalidPoolConfig
-        );
-    }
+    check_deadline(deadline, Clock::get()?.unix_timestamp)?;
+
+    validate_swap_accounts(ctx.accounts, is_a_to_b, native_sol)?;
 
     // Calculate output amount using constant product formula
-    let (reserve_in, reserve_out) = if is_a_to_b {
-        (pool.reserve_a, pool.reserve_b)
-    } else {
-        (pool.reserve_b, pool.reserve_a)
-    };
+    let pool = &ctx.accounts.pool;
+    let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
 
     let amount_out = AmmMath::get_amount_out(
         amount_in,
@@ -130,36 +117,176 @@ pub fn handler(
     // Check slippage tolerance
     require!(amount_out >= minimum_amount_out, AmmError::SlippageExceeded);
 
+    let spot_price_before = pool.get_spot_price()?;
+
+    execute_swap(ctx.accounts, amount_in, amount_out, is_a_to_b, native_sol)?;
+
+    // Check post-trade price impact
+    ctx.accounts.pool.check_price_impact(spot_price_before, max_price_impact_bps)
+}
+
+pub fn exact_out_handler(
+    ctx: Context<Swap>,
+    amount_out: u64,
+    maximum_amount_in: u64,
+    is_a_to_b: bool,
+    native_sol: bool,
+) -> Result<()> {
+    // Validate swap amount
+    require!(amount_out > 0, AmmError::ZeroAmount);
+
+    validate_swap_accounts(ctx.accounts, is_a_to_b, native_sol)?;
+
+    // Calculate required input amount using constant product formula
+    let pool = &ctx.accounts.pool;
+    let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+
+    let amount_in = AmmMath::get_amount_in(
+        amount_out,
+        reserve_in,
+        reserve_out,
+        pool.fee_numerator,
+        pool.fee_denominator,
+    )?;
+
+    // Check slippage tolerance
+    require!(amount_in <= maximum_amount_in, AmmError::SlippageExceeded);
+
+    execute_swap(ctx.accounts, amount_in, amount_out, is_a_to_b, native_sol)
+}
+
+/// Fail if the transaction is executed after its deadline
+pub(crate) fn check_deadline(deadline: Option<i64>, current_timestamp: i64) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require!(current_timestamp <= deadline, AmmError::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+/// Verify the pool is live and token accounts match the swap direction
+fn validate_swap_accounts(accounts: &Swap, is_a_to_b: bool, native_sol: bool) -> Result<()> {
+    let pool = &accounts.pool;
+
+    // Check if pool is paused
+    pool.require_not_paused(PAUSE_SWAPS)?;
+
+    let (input_mint, output_mint, input_vault, output_vault) = if is_a_to_b {
+        (pool.token_a_mint, pool.token_b_mint, pool.token_a_vault, pool.token_b_vault)
+    } else {
+        (pool.token_b_mint, pool.token_a_mint, pool.token_b_vault, pool.token_a_vault)
+    };
+
+    NativeSolHelper::require_native_pair(native_sol, &input_mint, &output_mint)?;
+
+    // A wSOL side handled natively goes through the temporary account only
+    let native_in = native_sol && NativeSolHelper::is_native(&input_mint);
+    let native_out = native_sol && NativeSolHelper::is_native(&output_mint);
+
+    require!(
+        native_in || accounts.user_token_in.is_some(),
+        AmmError::MissingTokenAccount
+    );
+    if let Some(user_token_in) = &accounts.user_token_in {
+        require!(user_token_in.mint == input_mint, AmmError::TokenMintMismatch);
+    }
+    require!(
+        accounts.pool_token_in.key() == input_vault,
+        AmmError::InvalidPoolConfig
+    );
+    require!(
+        accounts.pool_token_out.key() == output_vault,
+        AmmError::InvalidPoolConfig
+    );
+
+    // The output goes to a third-party recipient if one is given, otherwise to the user;
+    // whichever is passed must hold the pool's output mint
+    require!(
+        native_out || accounts.user_token_out.is_some() || accounts.recipient_token_out.is_some(),
+        AmmError::MissingOutputAccount
+    );
+    for token_out in [&accounts.user_token_out, &accounts.recipient_token_out]
+        .into_iter()
+        .flatten()
+    {
+        require!(token_out.mint == output_mint, AmmError::TokenMintMismatch);
+    }
+
+    Ok(())
+}
+
+/// Validate against the oracle, move tokens and update pool state for a priced swap.
+/// With `native_sol`, a wSOL input is funded from the user's lamports and a wSOL output is
+/// unwrapped to the user's lamports (not applied to a third-party recipient), both through
+/// the temporary wSOL account.
+fn execute_swap(
+    accounts: &mut Swap,
+    amount_in: u64,
+    amount_out: u64,
+    is_a_to_b: bool,
+    native_sol: bool,
+) -> Result<()> {
+    let (input_mint, output_mint) = if is_a_to_b {
+        (accounts.pool.token_a_mint, accounts.pool.token_b_mint)
+    } else {
+        (accounts.pool.token_b_mint, accounts.pool.token_a_mint)
+    };
+
+    // With native_sol, a wSOL input is funded from lamports through the temporary account
+    let source = NativeSolHelper::user_account(
+        native_sol,
+        &input_mint,
+        &accounts.user_token_in,
+        &accounts.wsol_temp,
+    )?;
+    if native_sol && NativeSolHelper::is_native(&input_mint) {
+        NativeSolHelper::wrap(
+            &accounts.system_program,
+            &accounts.token_program,
+            &accounts.user,
+            source,
+            amount_in,
+        )?;
+    }
+    let source = source.to_account_info();
+
+    let pool = &mut accounts.pool;
+    let clock = Clock::get()?;
+
     // Validate swap price against oracle
     OracleHelper::validate_swap_price(
         amount_in,
         amount_out,
-        &ctx.accounts.oracle_a,
-        &ctx.accounts.oracle_b,
+        &accounts.oracle_a,
+        &accounts.oracle_b,
         pool.oracle_max_age,
         pool.oracle_max_deviation_bps,
         is_a_to_b,
     )?;
 
-    // Calculate fee
-    let fee_amount = amount_in
-        .checked_mul(pool.fee_numerator)
-        .unwrap()
-        .checked_div(pool.fee_denominator)
-        .unwrap();
-
     // Transfer tokens from user to pool
     let transfer_in_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.user_token_in.to_account_info(),
-            to: ctx.accounts.pool_token_in.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
+            from: source,
+            to: accounts.pool_token_in.to_account_info(),
+            authority: accounts.user.to_account_info(),
         },
     );
     token::transfer(transfer_in_ctx, amount_in)?;
 
-    // Transfer tokens from pool to user
+    // Transfer tokens from pool to the recipient if one was given, otherwise to the user
+    // With native_sol, a wSOL output for the user is paid into the temporary account instead
+    let destination = match &accounts.recipient_token_out {
+        Some(recipient_token_out) => recipient_token_out.to_account_info(),
+        None => NativeSolHelper::user_account(
+            native_sol,
+            &output_mint,
+            &accounts.user_token_out,
+            &accounts.wsol_temp,
+        )?
+        .to_account_info(),
+    };
+
     let seeds = &[
         POOL_SEED,
         pool.token_a_mint.as_ref(),
@@ -169,32 +296,27 @@ pub fn handler(
     let signer = &[&seeds[..]];
 
     let transfer_out_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
+        accounts.token_program.to_account_info(),
         Transfer {
-            from: ctx.accounts.pool_token_out.to_account_info(),
-            to: ctx.accounts.user_token_out.to_account_info(),
+            from: accounts.pool_token_out.to_account_info(),
+            to: destination,
             authority: pool.to_account_info(),
         },
         signer,
     );
     token::transfer(transfer_out_ctx, amount_out)?;
 
-    // Update pool reserves
-    if is_a_to_b {
-        pool.reserve_a = pool.reserve_a.checked_add(amount_in).unwrap();
-        pool.reserve_b = pool.reserve_b.checked_sub(amount_out).unwrap();
-        pool.total_volume_a = pool.total_volume_a.checked_add(amount_in).unwrap();
-        pool.total_fees_a = pool.total_fees_a.checked_add(fee_amount).unwrap();
-    } else {
-        pool.reserve_b = pool.reserve_b.checked_add(amount_in).unwrap();
-        pool.reserve_a = pool.reserve_a.checked_sub(amount_out).unwrap();
-        pool.total_volume_b = pool.total_volume_b.checked_add(amount_in).unwrap();
-        pool.total_fees_b = pool.total_fees_b.checked_add(fee_amount).unwrap();
-    }
+    // Update pool reserves, volume and fees
+    let fee_amount = pool.record_swap(amount_in, amount_out, is_a_to_b)?;
 
     // Update TWAP
     pool.update_twap(clock.unix_timestamp)?;
 
+    // Close the temporary wSOL account, unwrapping any output and returning its rent
+    if let Some(wsol_temp) = &accounts.wsol_temp {
+        NativeSolHelper::close_temp_account(&accounts.token_program, wsol_temp, &accounts.user)?;
+    }
+
     msg!("Swap executed successfully");
     msg!("Amount in: {}, Amount out: {}", amount_in, amount_out);
     msg!("Fee collected: {}", fee_amount);
@@ -202,4 +324,3 @@ pub fn handler(
 
     Ok(())
 }
-
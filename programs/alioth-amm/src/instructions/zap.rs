@@ -0,0 +1,382 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use crate::constants::*;
+use crate::errors::AmmError;
+use crate::state::{LiquidityProvider, Pool};
+use crate::utils::{AmmMath, OracleHelper};
+
+// ========== Add Liquidity Single-Sided ==========
+
+/// Deposit a single token: part of it is swapped through the pool's own curve and the
+/// balanced remainder is added as liquidity
+#[derive(Accounts)]
+pub struct AddLiquiditySingleSided<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LiquidityProvider::LEN,
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub lp_provider: Account<'info, LiquidityProvider>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // User's account for the deposited token (A or B)
+    #[account(
+        mut,
+        constraint = user_token.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ AmmError::InvalidPoolConfig,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user,
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth oracle account for token A
+    #[account(
+        constraint = oracle_a.key() == pool.oracle_a @ AmmError::InvalidOracle,
+    )]
+    pub oracle_a: AccountInfo<'info>,
+
+    /// CHECK: Pyth oracle account for token B
+    #[account(
+        constraint = oracle_b.key() == pool.oracle_b @ AmmError::InvalidOracle,
+    )]
+    pub oracle_b: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_liquidity_single_sided_handler(
+    ctx: Context<AddLiquiditySingleSided>,
+    is_token_a: bool,
+    amount_in: u64,
+    min_liquidity: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let clock = Clock::get()?;
+
+    // Check if pool is paused
+    pool.require_not_paused(PAUSE_DEPOSITS | PAUSE_SWAPS)?;
+
+    // Validate amount
+    require!(amount_in > 0, AmmError::ZeroAmount);
+
+    // Zaps need an existing price to swap against
+    require!(pool.total_lp_supply > 0, AmmError::InsufficientLiquidity);
+
+    let (input_mint, vault_in) = if is_token_a {
+        (pool.token_a_mint, &ctx.accounts.token_a_vault)
+    } else {
+        (pool.token_b_mint, &ctx.accounts.token_b_vault)
+    };
+    require!(
+        ctx.accounts.user_token.mint == input_mint,
+        AmmError::TokenMintMismatch
+    );
+
+    // Swap the optimal fraction of the input into the other token
+    let (reserve_in, reserve_out) = pool.get_reserves(is_token_a);
+    let swap_amount = AmmMath::calculate_zap_swap_amount(
+        amount_in,
+        reserve_in,
+        pool.fee_numerator,
+        pool.fee_denominator,
+    )?;
+    let swap_out = AmmMath::get_amount_out(
+        swap_amount,
+        reserve_in,
+        reserve_out,
+        pool.fee_numerator,
+        pool.fee_denominator,
+    )?;
+
+    // Validate the internal swap with oracle
+    OracleHelper::validate_swap_price(
+        swap_amount,
+        swap_out,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        pool.oracle_max_age,
+        pool.oracle_max_deviation_bps,
+        is_token_a,
+    )?;
+
+    // Transfer the whole input to the pool; the swapped output never leaves the vault
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.user_token.to_account_info(),
+            to: vault_in.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, amount_in)?;
+
+    pool.record_swap(swap_amount, swap_out, is_token_a)?;
+
+    // Deposit the remainder and the swap output at the post-swap ratio
+    let deposit_in = amount_in - swap_amount;
+    let (amount_a, amount_b) = if is_token_a {
+        (deposit_in, swap_out)
+    } else {
+        (swap_out, deposit_in)
+    };
+    let (liquidity, _) = pool.record_deposit(amount_a, amount_b)?;
+
+    // Check slippage tolerance
+    require!(liquidity > 0, AmmError::ZeroAmount);
+    require!(liquidity >= min_liquidity, AmmError::SlippageExceeded);
+
+    // Mint LP tokens to user
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mint_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.user_lp_token.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        signer,
+    );
+    token::mint_to(mint_ctx, liquidity)?;
+
+    // Update TWAP
+    pool.update_twap(clock.unix_timestamp)?;
+
+    // Update or initialize LP provider state
+    let lp_provider = &mut ctx.accounts.lp_provider;
+    if lp_provider.lp_token_amount == 0 {
+        lp_provider.owner = ctx.accounts.user.key();
+        lp_provider.pool = pool.key();
+        lp_provider.initial_deposit_a = amount_a;
+        lp_provider.initial_deposit_b = amount_b;
+        lp_provider.created_at = clock.unix_timestamp;
+        lp_provider.bump = ctx.bumps.lp_provider;
+    }
+    // Cost basis is the single token the user paid in
+    let (deposited_a, deposited_b) = if is_token_a { (amount_in, 0) } else { (0, amount_in) };
+    lp_provider.record_deposit(liquidity, deposited_a, deposited_b, pool.fee_growth_index()?)?;
+
+    msg!("Single-sided liquidity added successfully");
+    msg!("Amount in: {}, Swapped: {}", amount_in, swap_amount);
+    msg!("Amount A: {}, Amount B: {}", amount_a, amount_b);
+    msg!("LP tokens minted: {}", liquidity);
+
+    Ok(())
+}
+
+// ========== Remove Liquidity Single-Sided ==========
+
+/// Burn LP tokens and receive a single token: the other side of the withdrawal is swapped
+/// through the pool's own curve
+#[derive(Accounts)]
+pub struct RemoveLiquiditySingleSided<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED,
+            pool.token_a_mint.as_ref(),
+            pool.token_b_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+
+    /// Optional analytics record; redemption only depends on burning LP tokens
+    #[account(
+        mut,
+        seeds = [
+            LP_PROVIDER_SEED,
+            pool.key().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump = lp_provider.bump,
+        constraint = lp_provider.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub lp_provider: Option<Account<'info, LiquidityProvider>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // User's account for the withdrawn token (A or B)
+    #[account(
+        mut,
+        constraint = user_token.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_token: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_a_vault.key() == pool.token_a_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_a_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = token_b_vault.key() == pool.token_b_vault @ AmmError::InvalidPoolConfig,
+    )]
+    pub token_b_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = lp_mint.key() == pool.lp_mint @ AmmError::InvalidPoolConfig,
+    )]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_lp_token.mint == lp_mint.key() @ AmmError::TokenMintMismatch,
+        constraint = user_lp_token.owner == user.key() @ AmmError::InvalidAuthority,
+    )]
+    pub user_lp_token: Account<'info, TokenAccount>,
+
+    /// CHECK: Pyth oracle account for token A
+    #[account(
+        constraint = oracle_a.key() == pool.oracle_a @ AmmError::InvalidOracle,
+    )]
+    pub oracle_a: AccountInfo<'info>,
+
+    /// CHECK: Pyth oracle account for token B
+    #[account(
+        constraint = oracle_b.key() == pool.oracle_b @ AmmError::InvalidOracle,
+    )]
+    pub oracle_b: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn remove_liquidity_single_sided_handler(
+    ctx: Context<RemoveLiquiditySingleSided>,
+    liquidity_amount: u64,
+    is_token_a: bool,
+    min_amount_out: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let clock = Clock::get()?;
+
+    // Check if pool is paused
+    pool.require_not_paused(PAUSE_WITHDRAWALS | PAUSE_SWAPS)?;
+
+    // Validate liquidity amount
+    require!(liquidity_amount > 0, AmmError::ZeroAmount);
+
+    let (output_mint, vault_out) = if is_token_a {
+        (pool.token_a_mint, &ctx.accounts.token_a_vault)
+    } else {
+        (pool.token_b_mint, &ctx.accounts.token_b_vault)
+    };
+    require!(
+        ctx.accounts.user_token.mint == output_mint,
+        AmmError::TokenMintMismatch
+    );
+
+    // Take the withdrawal out of reserves, then swap the unwanted side back in
+    let (amount_out, swap_amount, swap_out) =
+        pool.record_single_sided_withdrawal(liquidity_amount, is_token_a, min_amount_out)?;
+
+    // Validate the internal swap with oracle
+    OracleHelper::validate_swap_price(
+        swap_amount,
+        swap_out,
+        &ctx.accounts.oracle_a,
+        &ctx.accounts.oracle_b,
+        pool.oracle_max_age,
+        pool.oracle_max_deviation_bps,
+        !is_token_a,
+    )?;
+
+    // Burn LP tokens from user
+    let burn_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.user_lp_token.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::burn(burn_ctx, liquidity_amount)?;
+
+    // Transfer the requested token from pool to user
+    let seeds = &[
+        POOL_SEED,
+        pool.token_a_mint.as_ref(),
+        pool.token_b_mint.as_ref(),
+        &[pool.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: vault_out.to_account_info(),
+            to: ctx.accounts.user_token.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount_out)?;
+
+    // Update TWAP
+    pool.update_twap(clock.unix_timestamp)?;
+
+    // Update LP provider state
+    if let Some(lp_provider) = &mut ctx.accounts.lp_provider {
+        let (withdrawn_a, withdrawn_b) = if is_token_a { (amount_out, 0) } else { (0, amount_out) };
+        lp_provider.record_withdrawal(liquidity_amount, withdrawn_a, withdrawn_b);
+    }
+
+    msg!("Single-sided liquidity removed successfully");
+    msg!("LP tokens burned: {}", liquidity_amount);
+    msg!("Amount out: {}, Swapped in: {}", amount_out, swap_amount);
+
+    Ok(())
+}
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 
 pub mod constants;
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
 pub mod utils;
@@ -21,6 +22,7 @@ pub mod alioth_amm {
         fee_denominator: u64,
         oracle_max_age: i64,
         oracle_max_deviation_bps: u64,
+        lp_token_uri: String,
     ) -> Result<()> {
         instructions::initialize_pool::handler(
             ctx,
@@ -28,17 +30,27 @@ pub mod alioth_amm {
             fee_denominator,
             oracle_max_age,
             oracle_max_deviation_bps,
+            lp_token_uri,
         )
     }
 
     /// Add liquidity to a pool
     pub fn add_liquidity(
         ctx: Context<AddLiquidity>,
-        amount_a: u64,
-        amount_b: u64,
-        min_liquidity: u64,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        amount_a_min: u64,
+        amount_b_min: u64,
+        native_sol: bool,
     ) -> Result<()> {
-        instructions::add_liquidity::handler(ctx, amount_a, amount_b, min_liquidity)
+        instructions::add_liquidity::handler(
+            ctx,
+            amount_a_desired,
+            amount_b_desired,
+            amount_a_min,
+            amount_b_min,
+            native_sol,
+        )
     }
 
     /// Remove liquidity from a pool
@@ -47,8 +59,45 @@ pub mod alioth_amm {
         liquidity_amount: u64,
         min_amount_a: u64,
         min_amount_b: u64,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::remove_liquidity::handler(
+            ctx,
+            liquidity_amount,
+            min_amount_a,
+            min_amount_b,
+            native_sol,
+        )
+    }
+
+    /// Grow an LP position record created before cost basis tracking to the current layout
+    pub fn migrate_lp_provider(ctx: Context<MigrateLpProvider>) -> Result<()> {
+        instructions::migrate_lp_provider::handler(ctx)
+    }
+
+    /// Add liquidity from a single token, swapping the optimal part through the pool
+    pub fn add_liquidity_single_sided(
+        ctx: Context<AddLiquiditySingleSided>,
+        is_token_a: bool,
+        amount_in: u64,
+        min_liquidity: u64,
     ) -> Result<()> {
-        instructions::remove_liquidity::handler(ctx, liquidity_amount, min_amount_a, min_amount_b)
+        instructions::zap::add_liquidity_single_sided_handler(ctx, is_token_a, amount_in, min_liquidity)
+    }
+
+    /// Remove liquidity into a single token, swapping the other side through the pool
+    pub fn remove_liquidity_single_sided(
+        ctx: Context<RemoveLiquiditySingleSided>,
+        liquidity_amount: u64,
+        is_token_a: bool,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::zap::remove_liquidity_single_sided_handler(
+            ctx,
+            liquidity_amount,
+            is_token_a,
+            min_amount_out,
+        )
     }
 
     /// Swap tokens with oracle price validation
@@ -57,8 +106,30 @@ pub mod alioth_amm {
         amount_in: u64,
         minimum_amount_out: u64,
         is_a_to_b: bool,
+        deadline: Option<i64>,
+        max_price_impact_bps: Option<u64>,
+        native_sol: bool,
     ) -> Result<()> {
-        instructions::swap::handler(ctx, amount_in, minimum_amount_out, is_a_to_b)
+        instructions::swap::handler(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+            is_a_to_b,
+            deadline,
+            max_price_impact_bps,
+            native_sol,
+        )
+    }
+
+    /// Swap tokens for an exact output amount with oracle price validation
+    pub fn swap_exact_out(
+        ctx: Context<Swap>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+        is_a_to_b: bool,
+        native_sol: bool,
+    ) -> Result<()> {
+        instructions::swap::exact_out_handler(ctx, amount_out, maximum_amount_in, is_a_to_b, native_sol)
     }
 
     /// Execute a flash loan
@@ -72,38 +143,186 @@ pub mod alioth_amm {
     }
 
     /// Initialize farming pool for LP token staking
-    // pub fn initialize_farm(
-    //     ctx: Context<InitializeFarm>,
-    //     reward_per_slot: u64,
-    //     start_slot: u64,
-    //     end_slot: u64,
-    // ) -> Result<()> {
-    //     instructions::farming::initialize_farm_handler(ctx, reward_per_slot, start_slot, end_slot)
-    // }
+    pub fn initialize_farm(
+        ctx: Context<InitializeFarm>,
+        reward_per_second: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::farming::initialize_farm_handler(ctx, reward_per_second, start_time, end_time)
+    }
+
+    /// Deposit reward tokens into a farm's reward vault
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64) -> Result<()> {
+        instructions::farming::fund_farm_handler(ctx, amount)
+    }
 
     /// Stake LP tokens to earn rewards
-    // pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
-    //     instructions::farming::stake_handler(ctx, amount)
-    // }
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        instructions::farming::stake_handler(ctx, amount)
+    }
 
     /// Unstake LP tokens
-    // pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
-    //     instructions::farming::unstake_handler(ctx, amount)
-    // }
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        instructions::farming::unstake_handler(ctx, amount)
+    }
+
+    /// Withdraw all staked LP tokens, forfeiting pending rewards
+    pub fn emergency_unstake(ctx: Context<EmergencyUnstake>) -> Result<()> {
+        instructions::farming::emergency_unstake_handler(ctx)
+    }
 
     /// Claim farming rewards
-    // pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
-    //     instructions::farming::claim_rewards_handler(ctx)
-    // }
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::farming::claim_rewards_handler(ctx)
+    }
+
+    /// Add liquidity and stake the minted LP tokens in one instruction
+    pub fn add_liquidity_and_stake(
+        ctx: Context<AddLiquidityAndStake>,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        amount_a_min: u64,
+        amount_b_min: u64,
+    ) -> Result<()> {
+        instructions::liquidity_staking::add_liquidity_and_stake_handler(
+            ctx,
+            amount_a_desired,
+            amount_b_desired,
+            amount_a_min,
+            amount_b_min,
+        )
+    }
+
+    /// Unstake LP tokens and remove the underlying liquidity in one instruction
+    pub fn unstake_and_remove_liquidity(
+        ctx: Context<UnstakeAndRemoveLiquidity>,
+        liquidity_amount: u64,
+        min_amount_a: u64,
+        min_amount_b: u64,
+    ) -> Result<()> {
+        instructions::liquidity_staking::unstake_and_remove_liquidity_handler(
+            ctx,
+            liquidity_amount,
+            min_amount_a,
+            min_amount_b,
+        )
+    }
+
+    /// Pause farm reward accrual and new stakes (farm authority only)
+    pub fn pause_farm(ctx: Context<PauseFarm>) -> Result<()> {
+        instructions::farming::pause_farm_handler(ctx)
+    }
+
+    /// Resume a paused farm (farm authority only)
+    pub fn resume_farm(ctx: Context<ResumeFarm>) -> Result<()> {
+        instructions::farming::resume_farm_handler(ctx)
+    }
+
+    /// Close a finished, fully drained farm and reclaim its rent (farm authority only)
+    pub fn close_farm(ctx: Context<CloseFarm>) -> Result<()> {
+        instructions::farming::close_farm_handler(ctx)
+    }
+
+    /// Close an empty user stake account and reclaim its rent
+    pub fn close_user_stake(ctx: Context<CloseUserStake>) -> Result<()> {
+        instructions::farming::close_user_stake_handler(ctx)
+    }
 
     /// Multi-hop swap through multiple pools
-    pub fn multi_hop_swap(
-        ctx: Context<MultiHopSwap>,
+    pub fn multi_hop_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        route: Vec<SwapHop>,
+        deadline: Option<i64>,
+        max_price_impact_bps: Option<u64>,
+    ) -> Result<()> {
+        instructions::multi_hop::handler(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+            route,
+            deadline,
+            max_price_impact_bps,
+        )
+    }
+
+    /// Multi-hop swap moving tokens vault-to-vault between pools
+    pub fn multi_hop_swap_direct<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        route: Vec<SwapHop>,
+    ) -> Result<()> {
+        instructions::multi_hop::direct_handler(ctx, amount_in, minimum_amount_out, route)
+    }
+
+    /// Multi-hop swap for an exact final output amount
+    pub fn multi_hop_swap_exact_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MultiHopSwap<'info>>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+        route: Vec<SwapHop>,
+        direct: bool,
+    ) -> Result<()> {
+        instructions::multi_hop::exact_out_handler(ctx, amount_out, maximum_amount_in, route, direct)
+    }
+
+    /// Split a swap across parallel pools of the same pair
+    pub fn split_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SplitSwap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
-        hops: u8,
+        weights_bps: Vec<u16>,
     ) -> Result<()> {
-        instructions::multi_hop::handler(ctx, amount_in, minimum_amount_out, hops)
+        instructions::split_swap::handler(ctx, amount_in, minimum_amount_out, weights_bps)
+    }
+
+    /// Quote an exact-input swap against live pool state
+    pub fn quote_swap(ctx: Context<Quote>, amount_in: u64, is_a_to_b: bool) -> Result<SwapQuote> {
+        instructions::quote::quote_swap_handler(ctx, amount_in, is_a_to_b)
+    }
+
+    /// Quote an exact-output swap against live pool state
+    pub fn quote_swap_exact_out(
+        ctx: Context<Quote>,
+        amount_out: u64,
+        is_a_to_b: bool,
+    ) -> Result<SwapQuote> {
+        instructions::quote::quote_swap_exact_out_handler(ctx, amount_out, is_a_to_b)
+    }
+
+    /// Quote LP tokens minted for a deposit
+    pub fn quote_add_liquidity(
+        ctx: Context<Quote>,
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+    ) -> Result<LiquidityQuote> {
+        instructions::quote::quote_add_liquidity_handler(ctx, amount_a_desired, amount_b_desired)
+    }
+
+    /// Quote token amounts returned for burning LP tokens
+    pub fn quote_remove_liquidity(
+        ctx: Context<Quote>,
+        liquidity_amount: u64,
+    ) -> Result<WithdrawQuote> {
+        instructions::quote::quote_remove_liquidity_handler(ctx, liquidity_amount)
+    }
+
+    /// Value, fees earned and impermanent loss of an LP position
+    pub fn quote_position(ctx: Context<QuotePosition>) -> Result<PositionQuote> {
+        instructions::quote::quote_position_handler(ctx)
+    }
+
+    /// Send vault balances above the tracked reserves to the given accounts
+    pub fn skim(ctx: Context<SkimReserves>) -> Result<()> {
+        instructions::reconcile::skim_handler(ctx)
+    }
+
+    /// Reset reserves to the actual vault balances (admin only)
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        instructions::reconcile::sync_handler(ctx)
     }
 
     /// Pause pool (admin only)
@@ -116,6 +335,16 @@ pub mod alioth_amm {
         instructions::admin::unpause_pool_handler(ctx)
     }
 
+    /// Pause an exact set of operations (admin only)
+    pub fn set_pause_flags(ctx: Context<SetPauseFlags>, pause_flags: u8) -> Result<()> {
+        instructions::admin::set_pause_flags_handler(ctx, pause_flags)
+    }
+
+    /// Freeze every operation including withdrawals, with a justification (admin only)
+    pub fn freeze_pool(ctx: Context<FreezePool>, reason: String) -> Result<()> {
+        instructions::admin::freeze_pool_handler(ctx, reason)
+    }
+
     /// Update pool fees (admin only)
     pub fn update_fees(
         ctx: Context<UpdateFees>,
@@ -138,4 +367,14 @@ pub mod alioth_amm {
     ) -> Result<()> {
         instructions::admin::update_oracle_config_handler(ctx, new_max_age, new_max_deviation_bps)
     }
+
+    /// Update the LP token metadata URI (admin only)
+    pub fn update_lp_metadata_uri(ctx: Context<UpdateLpMetadataUri>, uri: String) -> Result<()> {
+        instructions::admin::update_lp_metadata_uri_handler(ctx, uri)
+    }
+
+    /// Close a pool with no outstanding LP and reclaim its rent (admin only)
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        instructions::admin::close_pool_handler(ctx)
+    }
 }
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::constants::REWARD_PRECISION;
+use crate::errors::AmmError;
 
 /// Farming pool for LP token staking
 #[account]
@@ -22,24 +24,30 @@ pub struct FarmingPool {
     /// Total LP tokens staked
     pub total_staked: u64,
     
-    /// Reward tokens distributed per slot
-    pub reward_per_slot: u64,
+    /// Reward tokens distributed per second
+    pub reward_per_second: u64,
     
-    /// Start slot for farming
-    pub start_slot: u64,
+    /// Unix timestamp when farming starts
+    pub start_time: i64,
     
-    /// End slot for farming
-    pub end_slot: u64,
+    /// Unix timestamp when farming ends
+    pub end_time: i64,
     
-    /// Last slot rewards were calculated
-    pub last_update_slot: u64,
+    /// Last timestamp rewards were calculated
+    pub last_update_time: i64,
     
     /// Accumulated rewards per LP token (scaled by 10^12 for precision)
     pub accumulated_reward_per_share: u128,
     
-    /// Total rewards distributed
+    /// Total rewards accrued to stakers (never exceeds `total_rewards_funded`)
     pub total_rewards_distributed: u64,
     
+    /// Total reward tokens deposited into the reward vault via `fund_farm`
+    pub total_rewards_funded: u64,
+    
+    /// Total rewards paid out to stakers
+    pub total_rewards_claimed: u64,
+    
     /// Whether the farm is active
     pub is_active: bool,
     
@@ -55,55 +63,81 @@ impl FarmingPool {
         32 + // reward_mint
         32 + // reward_vault
         8 + // total_staked
-        8 + // reward_per_slot
-        8 + // start_slot
-        8 + // end_slot
-        8 + // last_update_slot
+        8 + // reward_per_second
+        8 + // start_time
+        8 + // end_time
+        8 + // last_update_time
         16 + // accumulated_reward_per_share
         8 + // total_rewards_distributed
+        8 + // total_rewards_funded
+        8 + // total_rewards_claimed
         1 + // is_active
         1; // bump
 
-    /// Update reward calculations up to current slot
-    pub fn update_rewards(&mut self, current_slot: u64) -> Result<()> {
-        if self.total_staked == 0 {
-            self.last_update_slot = current_slot;
+    /// Funded rewards that have not yet been accrued to stakers
+    pub fn unallocated_rewards(&self) -> u64 {
+        self.total_rewards_funded
+            .saturating_sub(self.total_rewards_distributed)
+    }
+
+    /// Record `amount` reward tokens deposited into the reward vault
+    pub fn record_funding(&mut self, amount: u64) -> Result<()> {
+        self.total_rewards_funded = self.total_rewards_funded
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Update reward calculations up to the current unix timestamp
+    /// Accrual is capped at the rewards that have been funded, and rounding dust
+    /// is left unallocated rather than counted as distributed
+    pub fn update_rewards(&mut self, current_time: i64) -> Result<()> {
+        if current_time <= self.last_update_time {
             return Ok(());
         }
 
-        let slots_elapsed = if current_slot > self.last_update_slot {
-            let end_slot = std::cmp::min(current_slot, self.end_slot);
-            if end_slot > self.last_update_slot {
-                end_slot.checked_sub(self.last_update_slot).unwrap()
-            } else {
-                0
-            }
-        } else {
-            0
-        };
-
-        if slots_elapsed > 0 {
-            let rewards = (self.reward_per_slot as u128)
-                .checked_mul(slots_elapsed as u128)
-                .unwrap();
-
-            let reward_per_share = rewards
-                .checked_mul(1_000_000_000_000u128) // Scale by 10^12
-                .unwrap()
-                .checked_div(self.total_staked as u128)
-                .unwrap();
-
-            self.accumulated_reward_per_share = self.accumulated_reward_per_share
-                .checked_add(reward_per_share)
-                .unwrap();
-
-            self.total_rewards_distributed = self.total_rewards_distributed
-                .checked_add((rewards as u64).min(u64::MAX))
-                .unwrap();
-
-            self.last_update_slot = current_slot;
+        // Nothing accrues while the farm is paused or empty
+        let accrual_end = std::cmp::min(current_time, self.end_time);
+        if !self.is_active || self.total_staked == 0 || accrual_end <= self.last_update_time {
+            self.last_update_time = current_time;
+            return Ok(());
         }
 
+        let seconds_elapsed = accrual_end
+            .checked_sub(self.last_update_time)
+            .ok_or(AmmError::MathOverflow)?;
+
+        let emitted = (self.reward_per_second as u128)
+            .checked_mul(seconds_elapsed as u128)
+            .ok_or(AmmError::MathOverflow)?;
+
+        // Never accrue more than the vault has been funded with
+        let rewards = std::cmp::min(emitted, self.unallocated_rewards() as u128);
+
+        let reward_per_share = rewards
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(self.total_staked as u128)
+            .ok_or(AmmError::DivisionByZero)?;
+
+        // Rounded up so the sum over all updates always covers what stakers can claim;
+        // never exceeds `rewards` since the per-share amount was rounded down
+        let allocated = reward_per_share
+            .checked_mul(self.total_staked as u128)
+            .ok_or(AmmError::MathOverflow)?
+            .div_ceil(REWARD_PRECISION);
+
+        self.accumulated_reward_per_share = self.accumulated_reward_per_share
+            .checked_add(reward_per_share)
+            .ok_or(AmmError::MathOverflow)?;
+
+        self.total_rewards_distributed = self.total_rewards_distributed
+            .checked_add(u64::try_from(allocated).map_err(|_| AmmError::MathOverflow)?)
+            .ok_or(AmmError::MathOverflow)?;
+
+        self.last_update_time = current_time;
+
         Ok(())
     }
 
@@ -115,15 +149,97 @@ impl FarmingPool {
     ) -> Result<u64> {
         let total_accumulated = (staked_amount as u128)
             .checked_mul(self.accumulated_reward_per_share)
-            .unwrap()
-            .checked_div(1_000_000_000_000u128) // Unscale
-            .unwrap();
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(REWARD_PRECISION) // Unscale, rounding down
+            .ok_or(AmmError::DivisionByZero)?;
+
+        let pending = total_accumulated.saturating_sub(reward_debt);
 
-        let pending = total_accumulated
-            .checked_sub(reward_debt)
+        Ok(u64::try_from(pending).map_err(|_| AmmError::MathOverflow)?)
+    }
+
+    /// Settle pending rewards for `user_stake` and return the amount to pay out
+    /// Rewards must be updated before calling; the caller transfers the returned amount
+    pub fn settle_rewards(&mut self, user_stake: &mut UserStake) -> Result<u64> {
+        let pending = self.calculate_pending_rewards(
+            user_stake.staked_amount,
+            user_stake.reward_debt,
+        )?;
+
+        self.total_rewards_claimed = self.total_rewards_claimed
+            .checked_add(pending)
+            .ok_or(AmmError::MathOverflow)?;
+
+        // Payouts must always be backed by accrued (and therefore funded) rewards
+        require!(
+            self.total_rewards_claimed <= self.total_rewards_distributed,
+            AmmError::InsufficientRewardFunding
+        );
+
+        user_stake.total_rewards_claimed = user_stake.total_rewards_claimed
+            .checked_add(pending)
+            .ok_or(AmmError::MathOverflow)?;
+        user_stake.update_reward_debt(self.accumulated_reward_per_share)?;
+
+        Ok(pending)
+    }
+
+    /// Record `amount` LP tokens staked by `user_stake`
+    /// Rewards must be updated (and any pending rewards settled) before calling
+    pub fn add_stake(&mut self, user_stake: &mut UserStake, amount: u64) -> Result<()> {
+        user_stake.staked_amount = user_stake.staked_amount
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?;
+        user_stake.update_reward_debt(self.accumulated_reward_per_share)?;
+
+        self.total_staked = self.total_staked
+            .checked_add(amount)
+            .ok_or(AmmError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Record `amount` LP tokens unstaked by `user_stake`
+    /// Rewards must be updated (and any pending rewards settled) before calling
+    pub fn remove_stake(&mut self, user_stake: &mut UserStake, amount: u64) -> Result<()> {
+        require!(
+            user_stake.staked_amount >= amount,
+            AmmError::InsufficientStake
+        );
+
+        user_stake.staked_amount = user_stake.staked_amount
+            .checked_sub(amount)
+            .ok_or(AmmError::MathOverflow)?;
+        user_stake.update_reward_debt(self.accumulated_reward_per_share)?;
+
+        self.total_staked = self.total_staked
+            .checked_sub(amount)
+            .ok_or(AmmError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Clear `user_stake` entirely, forfeiting its pending rewards, and return the LP amount
+    /// to hand back. Never fails: rewards are brought up to date only if the reward math
+    /// succeeds, and the forfeited amount goes back to the unallocated budget so it is
+    /// emitted to the remaining stakers.
+    pub fn emergency_remove_stake(&mut self, user_stake: &mut UserStake, current_time: i64) -> u64 {
+        let mut updated = self.clone();
+        if updated.update_rewards(current_time).is_ok() {
+            *self = updated;
+        }
+
+        let forfeited = self
+            .calculate_pending_rewards(user_stake.staked_amount, user_stake.reward_debt)
             .unwrap_or(0);
+        self.total_rewards_distributed = self.total_rewards_distributed.saturating_sub(forfeited);
+
+        let amount = user_stake.staked_amount;
+        user_stake.staked_amount = 0;
+        user_stake.reward_debt = 0;
+        self.total_staked = self.total_staked.saturating_sub(amount);
 
-        Ok(pending as u64)
+        amount
     }
 }
 
@@ -147,7 +263,7 @@ pub struct UserStake {
     pub created_at: i64,
     
     /// Last time rewards were claimed
-    pub last_claim_slot: u64,
+    pub last_claim_time: i64,
     
     /// Total rewards claimed by user
     pub total_rewards_claimed: u64,
@@ -163,17 +279,186 @@ impl UserStake {
         8 + // staked_amount
         16 + // reward_debt
         8 + // created_at
-        8 + // last_claim_slot
+        8 + // last_claim_time
         8 + // total_rewards_claimed
         1; // bump
 
     /// Update reward debt after stake changes
-    pub fn update_reward_debt(&mut self, accumulated_reward_per_share: u128) {
+    /// Rounds up so that pending rewards (rounded down) can never exceed what was accrued
+    pub fn update_reward_debt(&mut self, accumulated_reward_per_share: u128) -> Result<()> {
         self.reward_debt = (self.staked_amount as u128)
             .checked_mul(accumulated_reward_per_share)
-            .unwrap()
-            .checked_div(1_000_000_000_000u128)
-            .unwrap();
+            .ok_or(AmmError::MathOverflow)?
+            .div_ceil(REWARD_PRECISION);
+
+        Ok(())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum FarmOp {
+        Stake(usize, u64),
+        Unstake(usize, u64),
+        Claim(usize),
+        EmergencyUnstake(usize),
+        Advance(i64),
+        Fund(u64),
+    }
+
+    fn farm_op() -> impl Strategy<Value = FarmOp> {
+        prop_oneof![
+            (0..4usize, 1..1_000_000_000_000u64).prop_map(|(user, amount)| FarmOp::Stake(user, amount)),
+            (0..4usize, 1..1_000_000_000_000u64).prop_map(|(user, amount)| FarmOp::Unstake(user, amount)),
+            (0..4usize).prop_map(FarmOp::Claim),
+            (0..4usize).prop_map(FarmOp::EmergencyUnstake),
+            (1..5_000i64).prop_map(FarmOp::Advance),
+            (1..1_000_000_000u64).prop_map(FarmOp::Fund),
+        ]
+    }
+
+    fn new_farm(reward_per_second: u64, duration: i64) -> FarmingPool {
+        FarmingPool {
+            reward_per_second,
+            start_time: 0,
+            end_time: duration,
+            last_update_time: 0,
+            is_active: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_accrual_capped_at_funding() {
+        let mut farm = new_farm(1_000, 10_000);
+        let mut stake = UserStake::default();
+        farm.record_funding(5_000).unwrap();
+        farm.add_stake(&mut stake, 100).unwrap();
+
+        // 100 seconds would emit 100_000 but only 5_000 has been funded
+        farm.update_rewards(100).unwrap();
+        assert_eq!(farm.total_rewards_distributed, 5_000);
+
+        let claimed = farm.settle_rewards(&mut stake).unwrap();
+        assert_eq!(claimed, 5_000);
+        assert_eq!(farm.unallocated_rewards(), 0);
+
+        // Nothing further accrues until the farm is topped up
+        farm.update_rewards(200).unwrap();
+        assert_eq!(farm.settle_rewards(&mut stake).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_no_accrual_while_paused() {
+        let mut farm = new_farm(10, 10_000);
+        let mut stake = UserStake::default();
+        farm.record_funding(1_000_000).unwrap();
+        farm.add_stake(&mut stake, 100).unwrap();
+
+        farm.update_rewards(100).unwrap();
+        farm.is_active = false;
+        farm.update_rewards(500).unwrap();
+        assert_eq!(farm.total_rewards_distributed, 1_000);
+
+        farm.is_active = true;
+        farm.update_rewards(600).unwrap();
+        assert_eq!(farm.total_rewards_distributed, 2_000);
+    }
+
+    #[test]
+    fn test_rounding_dust_is_not_distributed() {
+        let mut farm = new_farm(1, 10_000);
+        let mut stakes = [UserStake::default(), UserStake::default(), UserStake::default()];
+        farm.record_funding(1_000_000).unwrap();
+        for stake in stakes.iter_mut() {
+            farm.add_stake(stake, 1).unwrap();
+        }
+
+        // 1 reward over 3 LP tokens cannot be split without rounding
+        farm.update_rewards(1).unwrap();
+        let claimed: u64 = stakes
+            .iter_mut()
+            .map(|stake| farm.settle_rewards(stake).unwrap())
+            .sum();
+        assert!(claimed <= farm.total_rewards_distributed);
+        assert!(farm.total_rewards_distributed <= 1);
+    }
+
+    #[test]
+    fn test_emergency_unstake_returns_forfeited_rewards_to_budget() {
+        let mut farm = new_farm(10, 10_000);
+        let mut stakes = [UserStake::default(), UserStake::default()];
+        farm.record_funding(1_000_000).unwrap();
+        farm.add_stake(&mut stakes[0], 100).unwrap();
+        farm.add_stake(&mut stakes[1], 100).unwrap();
+
+        // Rewards up to t=100 are accrued before the stake is removed
+        assert_eq!(farm.emergency_remove_stake(&mut stakes[0], 100), 100);
+        assert_eq!(farm.last_update_time, 100);
+        assert_eq!(farm.total_staked, 100);
+        assert_eq!(stakes[0].staked_amount, 0);
+
+        // The forfeited half is unallocated again and re-emitted to the remaining staker
+        assert_eq!(farm.total_rewards_distributed, 500);
+        farm.update_rewards(200).unwrap();
+        assert_eq!(farm.settle_rewards(&mut stakes[1]).unwrap(), 1_500);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_claimed_never_exceeds_funded(
+            reward_per_second in 1..1_000_000_000u64,
+            duration in 3_600..2_592_000i64,
+            initial_funding in 0..1_000_000_000_000u64,
+            ops in prop::collection::vec(farm_op(), 1..200),
+        ) {
+            let mut farm = new_farm(reward_per_second, duration);
+            let mut stakes = vec![UserStake::default(); 4];
+            let mut now = 0i64;
+            let mut paid_out = 0u64;
+
+            farm.record_funding(initial_funding).unwrap();
+
+            for op in ops {
+                farm.update_rewards(now).unwrap();
+
+                match op {
+                    FarmOp::Stake(user, amount) => {
+                        paid_out += farm.settle_rewards(&mut stakes[user]).unwrap();
+                        farm.add_stake(&mut stakes[user], amount).unwrap();
+                    }
+                    FarmOp::Unstake(user, amount) => {
+                        let amount = amount % (stakes[user].staked_amount + 1);
+                        paid_out += farm.settle_rewards(&mut stakes[user]).unwrap();
+                        farm.remove_stake(&mut stakes[user], amount).unwrap();
+                    }
+                    FarmOp::Claim(user) => {
+                        paid_out += farm.settle_rewards(&mut stakes[user]).unwrap();
+                    }
+                    FarmOp::EmergencyUnstake(user) => {
+                        farm.emergency_remove_stake(&mut stakes[user], now);
+                    }
+                    FarmOp::Advance(seconds) => now += seconds,
+                    FarmOp::Fund(amount) => farm.record_funding(amount).unwrap(),
+                }
+
+                prop_assert_eq!(paid_out, farm.total_rewards_claimed);
+                prop_assert!(farm.total_rewards_claimed <= farm.total_rewards_distributed);
+                prop_assert!(farm.total_rewards_distributed <= farm.total_rewards_funded);
+            }
+
+            // Draining every position must still stay within what was deposited
+            now += duration;
+            farm.update_rewards(now).unwrap();
+            for stake in stakes.iter_mut() {
+                paid_out += farm.settle_rewards(stake).unwrap();
+            }
+            prop_assert!(paid_out <= farm.total_rewards_funded);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use crate::constants::{EMERGENCY_PAUSED_OPERATIONS, MINIMUM_LIQUIDITY, PAUSE_EMERGENCY, PRICE_PRECISION};
+use crate::errors::AmmError;
+use crate::utils::AmmMath;
 
 /// Main liquidity pool state account
 #[account]
@@ -49,8 +52,9 @@ pub struct Pool {
     /// Maximum allowed deviation from oracle price in basis points (1 bps = 0.01%)
     pub oracle_max_deviation_bps: u64,
     
-    /// Whether the pool is paused
-    pub is_paused: bool,
+    /// Bitmask of paused operations (`PAUSE_*` constants). This byte used to be an
+    /// `is_paused` bool; a pool paused before the flags existed reads as `PAUSE_EMERGENCY`.
+    pub pause_flags: u8,
     
     /// Cumulative price A (for TWAP calculation)
     pub cumulative_price_a: u128,
@@ -94,7 +98,7 @@ impl Pool {
         32 + // oracle_b
         8 + // oracle_max_age
         8 + // oracle_max_deviation_bps
-        1 + // is_paused
+        1 + // pause_flags
         16 + // cumulative_price_a
         16 + // cumulative_price_b
         8 + // last_update_timestamp
@@ -104,6 +108,34 @@ impl Pool {
         8 + // total_fees_b
         1; // bump
 
+    /// Whether any of the given `PAUSE_*` operations is paused, directly or by an emergency pause
+    pub fn is_paused(&self, flags: u8) -> bool {
+        let mut paused = self.pause_flags;
+        if paused & PAUSE_EMERGENCY != 0 {
+            paused |= EMERGENCY_PAUSED_OPERATIONS;
+        }
+        paused & flags != 0
+    }
+
+    /// Fail with `PoolPaused` if any of the given `PAUSE_*` operations is paused
+    pub fn require_not_paused(&self, flags: u8) -> Result<()> {
+        require!(!self.is_paused(flags), AmmError::PoolPaused);
+        Ok(())
+    }
+
+    /// Fail if the spot price moved more than `max_price_impact_bps` from `spot_price_before`
+    pub fn check_price_impact(
+        &self,
+        spot_price_before: u64,
+        max_price_impact_bps: Option<u64>,
+    ) -> Result<()> {
+        if let Some(max_price_impact_bps) = max_price_impact_bps {
+            let impact = AmmMath::calculate_price_impact_bps(spot_price_before, self.get_spot_price()?)?;
+            require!(impact <= max_price_impact_bps, AmmError::PriceImpactExceeded);
+        }
+        Ok(())
+    }
+
     /// Calculate the current price of token A in terms of token B
     pub fn get_spot_price(&self) -> Result<u64> {
         require!(self.reserve_a > 0 && self.reserve_b > 0, crate::errors::AmmError::InsufficientLiquidity);
@@ -118,6 +150,164 @@ impl Pool {
         Ok(price as u64)
     }
 
+    /// Calculate LP tokens to mint for a deposit of `amount_a` and `amount_b`
+    /// The first deposit permanently locks `MINIMUM_LIQUIDITY`
+    pub fn calculate_liquidity_to_mint(&self, amount_a: u64, amount_b: u64) -> Result<u64> {
+        if self.total_lp_supply == 0 {
+            // First liquidity provision
+            let initial_liquidity = AmmMath::calculate_initial_liquidity(amount_a, amount_b)?;
+
+            // Check minimum liquidity
+            require!(
+                initial_liquidity >= MINIMUM_LIQUIDITY,
+                AmmError::MinimumLiquidityNotMet
+            );
+
+            Ok(initial_liquidity - MINIMUM_LIQUIDITY)
+        } else {
+            // Subsequent liquidity provision
+            AmmMath::calculate_liquidity(
+                amount_a,
+                amount_b,
+                self.reserve_a,
+                self.reserve_b,
+                self.total_lp_supply,
+            )
+        }
+    }
+
+    /// Apply a deposit to reserves and LP supply.
+    /// Returns (LP tokens for the depositor, LP tokens to lock forever); the first deposit locks
+    /// `MINIMUM_LIQUIDITY`, and both amounts count towards `total_lp_supply` so it always
+    /// matches the LP mint's supply once they are minted.
+    pub fn record_deposit(&mut self, amount_a: u64, amount_b: u64) -> Result<(u64, u64)> {
+        let locked_liquidity = if self.total_lp_supply == 0 { MINIMUM_LIQUIDITY } else { 0 };
+        let liquidity = self.calculate_liquidity_to_mint(amount_a, amount_b)?;
+
+        self.reserve_a = self.reserve_a.checked_add(amount_a).ok_or(AmmError::MathOverflow)?;
+        self.reserve_b = self.reserve_b.checked_add(amount_b).ok_or(AmmError::MathOverflow)?;
+        self.total_lp_supply = self
+            .total_lp_supply
+            .checked_add(liquidity)
+            .and_then(|supply| supply.checked_add(locked_liquidity))
+            .ok_or(AmmError::MathOverflow)?;
+
+        Ok((liquidity, locked_liquidity))
+    }
+
+    /// Apply an LP burn to reserves and LP supply, returning the (amount_a, amount_b) withdrawn
+    pub fn record_withdrawal(&mut self, liquidity: u64) -> Result<(u64, u64)> {
+        let (amount_a, amount_b) = AmmMath::calculate_withdraw_amounts(
+            liquidity,
+            self.total_lp_supply,
+            self.reserve_a,
+            self.reserve_b,
+        )?;
+
+        self.reserve_a = self.reserve_a.checked_sub(amount_a).ok_or(AmmError::InsufficientLiquidity)?;
+        self.reserve_b = self.reserve_b.checked_sub(amount_b).ok_or(AmmError::InsufficientLiquidity)?;
+        self.total_lp_supply = self
+            .total_lp_supply
+            .checked_sub(liquidity)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+
+        Ok((amount_a, amount_b))
+    }
+
+    /// Withdraw `liquidity` entirely into one token: burn it, then swap the other side back
+    /// into the pool. Returns (amount_out, swap_amount_in, swap_amount_out) and fails with
+    /// `SlippageExceeded` if the total is below `min_amount_out`.
+    pub fn record_single_sided_withdrawal(
+        &mut self,
+        liquidity: u64,
+        is_token_a: bool,
+        min_amount_out: u64,
+    ) -> Result<(u64, u64, u64)> {
+        let (amount_a, amount_b) = self.record_withdrawal(liquidity)?;
+
+        // The swap runs from the other token into the requested one
+        let is_a_to_b = !is_token_a;
+        let (withdrawn_out, swap_amount) = if is_token_a {
+            (amount_a, amount_b)
+        } else {
+            (amount_b, amount_a)
+        };
+        let (reserve_in, reserve_out) = self.get_reserves(is_a_to_b);
+        let swap_out = AmmMath::get_amount_out(
+            swap_amount,
+            reserve_in,
+            reserve_out,
+            self.fee_numerator,
+            self.fee_denominator,
+        )?;
+
+        self.record_swap(swap_amount, swap_out, is_a_to_b)?;
+
+        let amount_out = withdrawn_out.checked_add(swap_out).ok_or(AmmError::MathOverflow)?;
+
+        // Check slippage tolerance
+        require!(amount_out >= min_amount_out, AmmError::SlippageExceeded);
+
+        Ok((amount_out, swap_amount, swap_out))
+    }
+
+    /// Vault balances above the tracked reserves, e.g. from direct donations
+    pub fn excess_balances(&self, vault_a_amount: u64, vault_b_amount: u64) -> (u64, u64) {
+        (
+            vault_a_amount.saturating_sub(self.reserve_a),
+            vault_b_amount.saturating_sub(self.reserve_b),
+        )
+    }
+
+    /// sqrt(reserve_a * reserve_b) per LP token (scaled by 10^9). Deposits and withdrawals
+    /// leave it unchanged, so it only grows as swap fees (or synced donations) accrue to reserves.
+    pub fn fee_growth_index(&self) -> Result<u128> {
+        if self.total_lp_supply == 0 {
+            return Ok(0);
+        }
+
+        let k = (self.reserve_a as u128)
+            .checked_mul(self.reserve_b as u128)
+            .ok_or(AmmError::MathOverflow)?;
+
+        Ok(AmmMath::sqrt(k)
+            .checked_mul(PRICE_PRECISION)
+            .ok_or(AmmError::MathOverflow)?
+            / self.total_lp_supply as u128)
+    }
+
+    /// Get (reserve_in, reserve_out) for the given swap direction
+    pub fn get_reserves(&self, is_a_to_b: bool) -> (u64, u64) {
+        if is_a_to_b {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        }
+    }
+
+    /// Apply a swap to reserves, volume and fee accounting, returning the fee charged
+    pub fn record_swap(&mut self, amount_in: u64, amount_out: u64, is_a_to_b: bool) -> Result<u64> {
+        let fee_amount = (amount_in as u128)
+            .checked_mul(self.fee_numerator as u128)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(self.fee_denominator as u128)
+            .ok_or(AmmError::DivisionByZero)? as u64;
+
+        if is_a_to_b {
+            self.reserve_a = self.reserve_a.checked_add(amount_in).ok_or(AmmError::MathOverflow)?;
+            self.reserve_b = self.reserve_b.checked_sub(amount_out).ok_or(AmmError::InsufficientLiquidity)?;
+            self.total_volume_a = self.total_volume_a.checked_add(amount_in).ok_or(AmmError::MathOverflow)?;
+            self.total_fees_a = self.total_fees_a.checked_add(fee_amount).ok_or(AmmError::MathOverflow)?;
+        } else {
+            self.reserve_b = self.reserve_b.checked_add(amount_in).ok_or(AmmError::MathOverflow)?;
+            self.reserve_a = self.reserve_a.checked_sub(amount_out).ok_or(AmmError::InsufficientLiquidity)?;
+            self.total_volume_b = self.total_volume_b.checked_add(amount_in).ok_or(AmmError::MathOverflow)?;
+            self.total_fees_b = self.total_fees_b.checked_add(fee_amount).ok_or(AmmError::MathOverflow)?;
+        }
+
+        Ok(fee_amount)
+    }
+
     /// Update TWAP accumulators
     pub fn update_twap(&mut self, current_timestamp: i64) -> Result<()> {
         if self.last_update_timestamp == 0 {
@@ -197,6 +387,21 @@ pub struct LiquidityProvider {
     
     /// Bump seed
     pub bump: u8,
+    
+    /// Cumulative token A deposited over the position's lifetime
+    pub total_deposited_a: u64,
+    
+    /// Cumulative token B deposited over the position's lifetime
+    pub total_deposited_b: u64,
+    
+    /// Cumulative token A withdrawn over the position's lifetime
+    pub total_withdrawn_a: u64,
+    
+    /// Cumulative token B withdrawn over the position's lifetime
+    pub total_withdrawn_b: u64,
+    
+    /// LP-weighted `Pool::fee_growth_index` at which the held LP tokens were minted
+    pub fee_growth_snapshot: u128,
 }
 
 impl LiquidityProvider {
@@ -207,6 +412,291 @@ impl LiquidityProvider {
         8 + // initial_deposit_a
         8 + // initial_deposit_b
         8 + // created_at
-        1; // bump
+        1 + // bump
+        8 + // total_deposited_a
+        8 + // total_deposited_b
+        8 + // total_withdrawn_a
+        8 + // total_withdrawn_b
+        16; // fee_growth_snapshot
+
+    /// Record LP tokens minted to the owner for a deposit of `amount_a`/`amount_b`,
+    /// averaging the fee growth snapshot by LP weight
+    pub fn record_deposit(
+        &mut self,
+        liquidity: u64,
+        amount_a: u64,
+        amount_b: u64,
+        fee_growth_index: u128,
+    ) -> Result<()> {
+        let lp_token_amount = self
+            .lp_token_amount
+            .checked_add(liquidity)
+            .ok_or(AmmError::MathOverflow)?;
+
+        if lp_token_amount > 0 {
+            self.fee_growth_snapshot = self
+                .fee_growth_snapshot
+                .checked_mul(self.lp_token_amount as u128)
+                .and_then(|held| {
+                    fee_growth_index
+                        .checked_mul(liquidity as u128)
+                        .and_then(|minted| held.checked_add(minted))
+                })
+                .ok_or(AmmError::MathOverflow)?
+                / lp_token_amount as u128;
+        }
+
+        self.lp_token_amount = lp_token_amount;
+        self.total_deposited_a = self.total_deposited_a.checked_add(amount_a).ok_or(AmmError::MathOverflow)?;
+        self.total_deposited_b = self.total_deposited_b.checked_add(amount_b).ok_or(AmmError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Record LP tokens redeemed by the owner for `amount_a`/`amount_b`. LP tokens can be
+    /// transferred, so the record saturates instead of blocking redemption of tokens it never saw.
+    pub fn record_withdrawal(&mut self, liquidity: u64, amount_a: u64, amount_b: u64) {
+        self.lp_token_amount = self.lp_token_amount.saturating_sub(liquidity);
+        self.total_withdrawn_a = self.total_withdrawn_a.saturating_add(amount_a);
+        self.total_withdrawn_b = self.total_withdrawn_b.saturating_add(amount_b);
+    }
+
+    /// Fill in the fields a pre-upgrade position lacks. Only the first deposit was recorded
+    /// then, so it seeds the cost basis; fees are tracked from `fee_growth_index` onwards.
+    pub fn seed_migrated_fields(&mut self, fee_growth_index: u128) {
+        self.total_deposited_a = self.initial_deposit_a;
+        self.total_deposited_b = self.initial_deposit_b;
+        self.fee_growth_snapshot = fee_growth_index;
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{PAUSE_DEPOSITS, PAUSE_FLASH_LOANS, PAUSE_SWAPS, PAUSE_WITHDRAWALS};
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum PoolOp {
+        Deposit(usize, u64, u64),
+        Withdraw(usize, u64),
+        Swap(u64, bool),
+    }
+
+    fn pool_op() -> impl Strategy<Value = PoolOp> {
+        prop_oneof![
+            (0..4usize, 1..1_000_000_000_000u64, 1..1_000_000_000_000u64)
+                .prop_map(|(user, amount_a, amount_b)| PoolOp::Deposit(user, amount_a, amount_b)),
+            (0..4usize, 1..u64::MAX).prop_map(|(user, amount)| PoolOp::Withdraw(user, amount)),
+            (1..1_000_000_000_000u64, any::<bool>()).prop_map(|(amount, is_a_to_b)| PoolOp::Swap(amount, is_a_to_b)),
+        ]
+    }
+
+    fn new_pool() -> Pool {
+        Pool {
+            fee_numerator: 3,
+            fee_denominator: 1000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_legacy_paused_byte_reads_as_emergency_pause() {
+        // Pools paused through the old `is_paused` bool store 1
+        let pool = Pool {
+            pause_flags: 1,
+            ..new_pool()
+        };
+
+        assert!(pool.is_paused(PAUSE_SWAPS));
+        assert!(pool.is_paused(PAUSE_DEPOSITS));
+        assert!(pool.is_paused(PAUSE_FLASH_LOANS));
+        assert!(!pool.is_paused(PAUSE_WITHDRAWALS));
+
+        let pool = Pool {
+            pause_flags: PAUSE_SWAPS,
+            ..new_pool()
+        };
+        assert!(pool.is_paused(PAUSE_SWAPS));
+        assert!(!pool.is_paused(PAUSE_DEPOSITS));
+    }
+
+    #[test]
+    fn test_first_deposit_locks_minimum_liquidity() {
+        let mut pool = new_pool();
+
+        let (liquidity, locked) = pool.record_deposit(1_000_000, 4_000_000).unwrap();
+        assert_eq!(locked, MINIMUM_LIQUIDITY);
+        assert_eq!(liquidity, 2_000_000 - MINIMUM_LIQUIDITY);
+        assert_eq!(pool.total_lp_supply, 2_000_000);
+
+        // Later deposits lock nothing
+        let (liquidity, locked) = pool.record_deposit(500_000, 2_000_000).unwrap();
+        assert_eq!(locked, 0);
+        assert_eq!(liquidity, 1_000_000);
+        assert_eq!(pool.total_lp_supply, 3_000_000);
+    }
+
+    #[test]
+    fn test_locked_liquidity_keeps_reserves_after_full_withdrawal() {
+        let mut pool = new_pool();
+        let (liquidity, _) = pool.record_deposit(1_000_000, 4_000_000).unwrap();
+
+        let (amount_a, amount_b) = pool.record_withdrawal(liquidity).unwrap();
+        assert_eq!(pool.total_lp_supply, MINIMUM_LIQUIDITY);
+        assert_eq!(pool.reserve_a, 1_000_000 - amount_a);
+        assert_eq!(pool.reserve_b, 4_000_000 - amount_b);
+        assert!(pool.reserve_a > 0 && pool.reserve_b > 0);
+    }
+
+    #[test]
+    fn test_single_sided_withdrawal_respects_min_amount_out() {
+        let mut pool = new_pool();
+        let (liquidity, _) = pool.record_deposit(1_000_000, 4_000_000).unwrap();
+
+        let (amount_out, swap_amount, swap_out) = pool
+            .clone()
+            .record_single_sided_withdrawal(liquidity / 2, false, 0)
+            .unwrap();
+        assert!(swap_amount > 0 && swap_out > 0);
+        // Swapping back in costs fees and price impact, so less than the proportional value
+        assert!(amount_out < 4_000_000);
+
+        assert!(pool
+            .clone()
+            .record_single_sided_withdrawal(liquidity / 2, false, amount_out)
+            .is_ok());
+        assert_eq!(
+            pool.record_single_sided_withdrawal(liquidity / 2, false, amount_out + 1)
+                .unwrap_err(),
+            AmmError::SlippageExceeded.into()
+        );
+    }
+
+    #[test]
+    fn test_excess_balances_ignores_shortfalls() {
+        let mut pool = new_pool();
+        pool.record_deposit(1_000_000, 4_000_000).unwrap();
+
+        assert_eq!(pool.excess_balances(1_000_500, 4_000_000), (500, 0));
+        // A vault below its reserve (mid flash loan) has no surplus
+        assert_eq!(pool.excess_balances(900_000, 4_000_010), (0, 10));
+    }
+
+    #[test]
+    fn test_fee_growth_snapshot_is_lp_weighted() {
+        let mut lp_provider = LiquidityProvider::default();
+
+        lp_provider.record_deposit(1_000, 10, 10, 1_000_000_000).unwrap();
+        assert_eq!(lp_provider.fee_growth_snapshot, 1_000_000_000);
+
+        lp_provider.record_deposit(3_000, 30, 30, 1_200_000_000).unwrap();
+        assert_eq!(lp_provider.fee_growth_snapshot, 1_150_000_000);
+        assert_eq!(lp_provider.total_deposited_a, 40);
+
+        // Withdrawals keep the snapshot and accumulate amounts out
+        lp_provider.record_withdrawal(2_000, 25, 20);
+        assert_eq!(lp_provider.lp_token_amount, 2_000);
+        assert_eq!(lp_provider.fee_growth_snapshot, 1_150_000_000);
+        assert_eq!((lp_provider.total_withdrawn_a, lp_provider.total_withdrawn_b), (25, 20));
+    }
+
+    #[test]
+    fn test_migrated_position_seeds_cost_basis_from_initial_deposit() {
+        let mut lp_provider = LiquidityProvider {
+            lp_token_amount: 1_000,
+            initial_deposit_a: 500,
+            initial_deposit_b: 2_000,
+            ..Default::default()
+        };
+
+        lp_provider.seed_migrated_fields(1_100_000_000);
+
+        assert_eq!(
+            (lp_provider.total_deposited_a, lp_provider.total_deposited_b),
+            (500, 2_000)
+        );
+        assert_eq!(
+            (lp_provider.total_withdrawn_a, lp_provider.total_withdrawn_b),
+            (0, 0)
+        );
+        assert_eq!(lp_provider.fee_growth_snapshot, 1_100_000_000);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_total_lp_supply_matches_minted(ops in prop::collection::vec(pool_op(), 1..200)) {
+            let mut pool = new_pool();
+            let mut balances = [0u64; 4];
+            let mut locked_balance = 0u64;
+            let mut mint_supply = 0u64;
+
+            for op in ops {
+                // Failed instructions revert, so only successful ops touch state
+                let snapshot = pool.clone();
+
+                match op {
+                    PoolOp::Deposit(user, amount_a, amount_b) => {
+                        let deposit = if pool.total_lp_supply == 0 {
+                            Ok((amount_a, amount_b))
+                        } else {
+                            AmmMath::calculate_optimal_deposit(
+                                amount_a, amount_b, 0, 0, pool.reserve_a, pool.reserve_b,
+                            )
+                        };
+                        match deposit.and_then(|(a, b)| pool.record_deposit(a, b)) {
+                            Ok((liquidity, locked)) => {
+                                balances[user] += liquidity;
+                                locked_balance += locked;
+                                mint_supply += liquidity + locked;
+                            }
+                            Err(_) => pool = snapshot,
+                        }
+                    }
+                    PoolOp::Withdraw(user, amount) => {
+                        let liquidity = amount % (balances[user] + 1);
+                        if liquidity == 0 {
+                            continue;
+                        }
+                        match pool.record_withdrawal(liquidity) {
+                            Ok(_) => {
+                                balances[user] -= liquidity;
+                                mint_supply -= liquidity;
+                            }
+                            Err(_) => pool = snapshot,
+                        }
+                    }
+                    PoolOp::Swap(amount_in, is_a_to_b) => {
+                        let (reserve_in, reserve_out) = pool.get_reserves(is_a_to_b);
+                        let swapped = AmmMath::get_amount_out(
+                            amount_in, reserve_in, reserve_out, pool.fee_numerator, pool.fee_denominator,
+                        )
+                        .and_then(|amount_out| pool.record_swap(amount_in, amount_out, is_a_to_b));
+                        if swapped.is_err() {
+                            pool = snapshot;
+                        }
+                    }
+                }
+
+                prop_assert_eq!(pool.total_lp_supply, mint_supply);
+                prop_assert_eq!(mint_supply, balances.iter().sum::<u64>() + locked_balance);
+                if pool.total_lp_supply > 0 {
+                    prop_assert_eq!(locked_balance, MINIMUM_LIQUIDITY);
+                    prop_assert!(pool.reserve_a > 0 && pool.reserve_b > 0);
+                }
+            }
+
+            // Redeeming every holder's LP never touches the locked amount
+            for balance in balances.iter_mut() {
+                if *balance > 0 {
+                    pool.record_withdrawal(*balance).unwrap();
+                    mint_supply -= *balance;
+                    *balance = 0;
+                }
+            }
+            prop_assert_eq!(pool.total_lp_supply, locked_balance);
+            prop_assert_eq!(pool.total_lp_supply, mint_supply);
+        }
+    }
+}
@@ -109,6 +109,89 @@ impl AmmMath {
         Ok(std::cmp::min(liquidity_a, liquidity_b) as u64)
     }
 
+    /// Amount of the other token matching `amount_a` at the current reserve ratio
+    /// Formula: amount_a * reserve_b / reserve_a
+    pub fn quote(amount_a: u64, reserve_a: u64, reserve_b: u64) -> Result<u64> {
+        require!(reserve_a > 0 && reserve_b > 0, AmmError::InsufficientLiquidity);
+
+        let amount_b = (amount_a as u128)
+            .checked_mul(reserve_b as u128)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(reserve_a as u128)
+            .ok_or(AmmError::DivisionByZero)?;
+
+        u64::try_from(amount_b).map_err(|_| error!(AmmError::MathOverflow))
+    }
+
+    /// Largest deposit within the desired amounts that matches the reserve ratio
+    /// Empty pools take the desired amounts as-is
+    pub fn calculate_optimal_deposit(
+        amount_a_desired: u64,
+        amount_b_desired: u64,
+        amount_a_min: u64,
+        amount_b_min: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+    ) -> Result<(u64, u64)> {
+        if reserve_a == 0 && reserve_b == 0 {
+            return Ok((amount_a_desired, amount_b_desired));
+        }
+
+        let amount_b_optimal = Self::quote(amount_a_desired, reserve_a, reserve_b)?;
+        if amount_b_optimal <= amount_b_desired {
+            require!(amount_b_optimal >= amount_b_min, AmmError::SlippageExceeded);
+            return Ok((amount_a_desired, amount_b_optimal));
+        }
+
+        let amount_a_optimal = Self::quote(amount_b_desired, reserve_b, reserve_a)?;
+        // Invariant: amount_b_optimal > amount_b_desired means
+        // amount_b_desired * reserve_a / reserve_b < amount_a_desired, and quote rounds down
+        require!(amount_a_optimal <= amount_a_desired, AmmError::InsufficientLiquidity);
+        require!(amount_a_optimal >= amount_a_min, AmmError::SlippageExceeded);
+        Ok((amount_a_optimal, amount_b_desired))
+    }
+
+    /// Portion of a single-sided deposit to swap so the remainder matches the post-swap ratio
+    /// Formula (f = fee_denominator, g = f - fee_numerator):
+    /// (sqrt(r * (r * (f + g)^2 + 4 * f * g * amount_in)) - r * (f + g)) / (2 * g)
+    pub fn calculate_zap_swap_amount(
+        amount_in: u64,
+        reserve_in: u64,
+        fee_numerator: u64,
+        fee_denominator: u64,
+    ) -> Result<u64> {
+        require!(reserve_in > 0, AmmError::InsufficientLiquidity);
+
+        let fee_denominator = fee_denominator as u128;
+        let fee_multiplier = fee_denominator
+            .checked_sub(fee_numerator as u128)
+            .ok_or(AmmError::MathOverflow)?;
+        let fee_sum = fee_denominator
+            .checked_add(fee_multiplier)
+            .ok_or(AmmError::MathOverflow)?;
+        let reserve_in = reserve_in as u128;
+
+        let inner = reserve_in
+            .checked_mul(fee_sum.checked_mul(fee_sum).ok_or(AmmError::MathOverflow)?)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_add(
+                (amount_in as u128)
+                    .checked_mul(4 * fee_denominator * fee_multiplier)
+                    .ok_or(AmmError::MathOverflow)?,
+            )
+            .ok_or(AmmError::MathOverflow)?
+            .checked_mul(reserve_in)
+            .ok_or(AmmError::MathOverflow)?;
+
+        let swap_amount = Self::sqrt(inner)
+            .checked_sub(reserve_in.checked_mul(fee_sum).ok_or(AmmError::MathOverflow)?)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(2 * fee_multiplier)
+            .ok_or(AmmError::DivisionByZero)?;
+
+        u64::try_from(swap_amount).map_err(|_| error!(AmmError::MathOverflow))
+    }
+
     /// Calculate amounts to withdraw given liquidity tokens
     pub fn calculate_withdraw_amounts(
         liquidity: u64,
@@ -170,6 +253,22 @@ impl AmmMath {
         Ok(deviation as u64)
     }
 
+    /// Calculate how far a price moved from its starting value, in basis points
+    pub fn calculate_price_impact_bps(price_before: u64, price_after: u64) -> Result<u64> {
+        if price_before == 0 {
+            return Ok(10000); // Treat a zero starting price as a full move
+        }
+
+        let impact = (price_before as u128)
+            .abs_diff(price_after as u128)
+            .checked_mul(10000u128)
+            .ok_or(AmmError::MathOverflow)?
+            .checked_div(price_before as u128)
+            .ok_or(AmmError::DivisionByZero)?;
+
+        Ok(u64::try_from(impact).unwrap_or(u64::MAX))
+    }
+
     /// Apply basis points to an amount
     pub fn apply_bps(amount: u64, bps: u64) -> Result<u64> {
         let calculated = (amount as u128)
@@ -202,10 +301,76 @@ mod tests {
         // With 0.3% fee (numerator=3, denominator=1000)
         // Input: 100, Reserve In: 1000, Reserve Out: 1000
         // After fee: 100 * 997 / 1000 = 99.7
-        // Output: (99.7 * 1000) / (1000 + 99.7) ≈ 90.66
-        let result = AmmMath::get_amount_out(100, 1000, 1000, 3, 1000);
-        assert!(result.is_ok());
-        assert!(result.unwrap() > 90 && result.unwrap() < 91);
+        // Output: (99.7 * 1000) / (1000 + 99.7) ≈ 90.66, rounded down
+        let amount_out = AmmMath::get_amount_out(100, 1000, 1000, 3, 1000).unwrap();
+        assert_eq!(amount_out, 90);
     }
-}
 
+    #[test]
+    fn test_get_amount_in_covers_amount_out() {
+        // The input quoted for an exact output must buy at least that output
+        for &(amount_out, reserve_in, reserve_out) in &[
+            (90u64, 1000u64, 1000u64),
+            (1, 1_000_000, 1_000_000),
+            (999_999, 5_000_000_000, 1_000_000_000),
+            (123_456_789, 10_000_000_000_000, 7_000_000_000_000),
+        ] {
+            let amount_in = AmmMath::get_amount_in(amount_out, reserve_in, reserve_out, 3, 1000).unwrap();
+            let received = AmmMath::get_amount_out(amount_in, reserve_in, reserve_out, 3, 1000).unwrap();
+            assert!(received >= amount_out);
+        }
+
+        // Cannot buy the entire reserve
+        assert!(AmmMath::get_amount_in(1000, 1000, 1000, 3, 1000).is_err());
+    }
+
+    #[test]
+    fn test_calculate_price_impact_bps() {
+        assert_eq!(AmmMath::calculate_price_impact_bps(1000, 1000).unwrap(), 0);
+        assert_eq!(AmmMath::calculate_price_impact_bps(1000, 1100).unwrap(), 1000);
+        assert_eq!(AmmMath::calculate_price_impact_bps(1000, 900).unwrap(), 1000);
+        assert_eq!(AmmMath::calculate_price_impact_bps(1000, 3000).unwrap(), 20000);
+        assert_eq!(AmmMath::calculate_price_impact_bps(0, 1000).unwrap(), 10000);
+    }
+
+    #[test]
+    fn test_calculate_optimal_deposit() {
+        // Pool at a 1:2 ratio; the over-supplied side is trimmed
+        assert_eq!(
+            AmmMath::calculate_optimal_deposit(100, 500, 0, 0, 1000, 2000).unwrap(),
+            (100, 200)
+        );
+        assert_eq!(
+            AmmMath::calculate_optimal_deposit(500, 100, 0, 0, 1000, 2000).unwrap(),
+            (50, 100)
+        );
+
+        // Empty pool takes the desired amounts
+        assert_eq!(
+            AmmMath::calculate_optimal_deposit(500, 100, 0, 0, 0, 0).unwrap(),
+            (500, 100)
+        );
+
+        // Trimmed side below its minimum
+        assert!(AmmMath::calculate_optimal_deposit(100, 500, 0, 300, 1000, 2000).is_err());
+        assert!(AmmMath::calculate_optimal_deposit(500, 100, 60, 0, 1000, 2000).is_err());
+    }
+
+    #[test]
+    fn test_calculate_zap_swap_amount_balances_deposit() {
+        for &(amount_in, reserve_in, reserve_out) in &[
+            (1_000_000u64, 10_000_000u64, 20_000_000u64),
+            (5_000_000_000, 1_000_000_000_000, 3_000_000_000_000),
+            (1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000),
+        ] {
+            let swap_amount = AmmMath::calculate_zap_swap_amount(amount_in, reserve_in, 3, 1000).unwrap();
+            let swap_out = AmmMath::get_amount_out(swap_amount, reserve_in, reserve_out, 3, 1000).unwrap();
+
+            // The remaining input and the swap output match the post-swap reserve ratio
+            let total_supply = 1_000_000_000;
+            let liquidity_in = (amount_in - swap_amount) as u128 * total_supply / (reserve_in + swap_amount) as u128;
+            let liquidity_out = swap_out as u128 * total_supply / (reserve_out - swap_out) as u128;
+            assert!(liquidity_in.abs_diff(liquidity_out) * 10_000 <= liquidity_in.max(liquidity_out));
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::MetadataAccount;
+use crate::constants::*;
+use crate::errors::AmmError;
+
+/// Number of base58 characters of a mint address used when the mint has no metadata symbol
+const MINT_SYMBOL_FALLBACK_LEN: usize = 4;
+
+/// Helpers for the Metaplex Token Metadata account of a pool's LP mint
+pub struct TokenMetadataHelper;
+
+impl TokenMetadataHelper {
+    /// Symbol naming one side of a pair in the LP token name. Taken from the mint's own
+    /// Metaplex metadata, so the LP name can't claim a different pair; mints without metadata
+    /// (or with an empty symbol) fall back to the start of the mint address.
+    pub fn pair_symbol(metadata: Option<&MetadataAccount>, mint: &Pubkey) -> String {
+        let symbol = metadata
+            .map(|metadata| metadata.symbol.trim_end_matches('\0').trim().to_string())
+            .unwrap_or_default();
+
+        if symbol.is_empty() {
+            mint.to_string()[..MINT_SYMBOL_FALLBACK_LEN].to_string()
+        } else {
+            symbol
+        }
+    }
+
+    /// LP token name for a pair, e.g. "ALIOTH-SOL/USDC-LP"
+    pub fn lp_token_name(symbol_a: &str, symbol_b: &str) -> Result<String> {
+        require!(
+            !symbol_a.trim().is_empty() && !symbol_b.trim().is_empty(),
+            AmmError::InvalidLpMetadata
+        );
+
+        let name = format!("{}-{}/{}-LP", LP_TOKEN_NAME_PREFIX, symbol_a.trim(), symbol_b.trim());
+        require!(name.len() <= MAX_METADATA_NAME_LEN, AmmError::InvalidLpMetadata);
+
+        Ok(name)
+    }
+
+    /// Token Metadata data for an LP mint: no royalties, creators, collection or uses
+    pub fn lp_token_data(name: String, uri: String) -> Result<DataV2> {
+        require!(uri.len() <= MAX_METADATA_URI_LEN, AmmError::InvalidLpMetadata);
+
+        Ok(DataV2 {
+            name,
+            symbol: LP_TOKEN_SYMBOL.to_string(),
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        })
+    }
+
+    /// Stored name, which Token Metadata pads with trailing NULs
+    pub fn stored_name(metadata: &MetadataAccount) -> String {
+        metadata.name.trim_end_matches('\0').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lp_token_name() {
+        assert_eq!(
+            TokenMetadataHelper::lp_token_name("SOL", "USDC").unwrap(),
+            "ALIOTH-SOL/USDC-LP"
+        );
+        assert!(TokenMetadataHelper::lp_token_name("", "USDC").is_err());
+        assert!(TokenMetadataHelper::lp_token_name("VERYLONGSYMBOL", "ANOTHERLONGONE").is_err());
+    }
+
+    #[test]
+    fn test_pair_symbol_falls_back_to_mint_address() {
+        let mint = anchor_spl::token::spl_token::native_mint::ID;
+        assert_eq!(TokenMetadataHelper::pair_symbol(None, &mint), "So11");
+
+        // Token Metadata caps symbols at 10 bytes, so any two always fit in the LP name
+        let name = TokenMetadataHelper::lp_token_name("ABCDEFGHIJ", "KLMNOPQRST").unwrap();
+        assert!(name.len() <= MAX_METADATA_NAME_LEN);
+    }
+}
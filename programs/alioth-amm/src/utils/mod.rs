@@ -1,6 +1,9 @@
 pub mod math;
+pub mod metadata;
+pub mod native_sol;
 pub mod oracle;
 
 pub use math::*;
+pub use metadata::*;
+pub use native_sol::*;
 pub use oracle::*;
-
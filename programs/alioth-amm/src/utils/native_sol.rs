@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, spl_token::native_mint, CloseAccount, SyncNative, Token, TokenAccount};
+use crate::errors::AmmError;
+
+/// Helpers for wrapping and unwrapping native SOL inside an instruction.
+///
+/// SOL only ever passes through a temporary wSOL account (`[WSOL_TEMP_SEED, user]`) that the
+/// instruction creates and closes again; the caller's own token accounts are never closed, and
+/// a wSOL token account of their own is not needed at all.
+pub struct NativeSolHelper;
+
+impl NativeSolHelper {
+    /// Whether the mint is wrapped SOL
+    pub fn is_native(mint: &Pubkey) -> bool {
+        *mint == native_mint::ID
+    }
+
+    /// Fail unless one side of the pair is wSOL when `native_sol` is requested
+    pub fn require_native_pair(native_sol: bool, mint_a: &Pubkey, mint_b: &Pubkey) -> Result<()> {
+        require!(
+            !native_sol || Self::is_native(mint_a) || Self::is_native(mint_b),
+            AmmError::TokenMintMismatch
+        );
+        Ok(())
+    }
+
+    /// The account the user's side of `mint` moves through: the temporary wSOL account when
+    /// `native_sol` is set and `mint` is wSOL, otherwise the user's own token account
+    pub fn user_account<'a, 'info>(
+        native_sol: bool,
+        mint: &Pubkey,
+        user_token: &'a Option<Account<'info, TokenAccount>>,
+        wsol_temp: &'a Option<Account<'info, TokenAccount>>,
+    ) -> Result<&'a Account<'info, TokenAccount>> {
+        if native_sol && Self::is_native(mint) {
+            Self::temp_account(wsol_temp)
+        } else {
+            user_token
+                .as_ref()
+                .ok_or_else(|| error!(AmmError::MissingTokenAccount))
+        }
+    }
+
+    /// The temporary wSOL account, which must be passed whenever SOL is wrapped or unwrapped
+    pub fn temp_account<'a, 'info>(
+        wsol_temp: &'a Option<Account<'info, TokenAccount>>,
+    ) -> Result<&'a Account<'info, TokenAccount>> {
+        wsol_temp
+            .as_ref()
+            .ok_or_else(|| error!(AmmError::MissingNativeSolAccount))
+    }
+
+    /// Move `amount` lamports from the signer into the temporary wSOL account and sync its
+    /// token balance
+    pub fn wrap<'info>(
+        system_program: &Program<'info, System>,
+        token_program: &Program<'info, Token>,
+        from: &Signer<'info>,
+        wsol_temp: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        let transfer_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: from.to_account_info(),
+                to: wsol_temp.to_account_info(),
+            },
+        );
+        system_program::transfer(transfer_ctx, amount)?;
+
+        let sync_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            SyncNative {
+                account: wsol_temp.to_account_info(),
+            },
+        );
+        token::sync_native(sync_ctx)?;
+
+        msg!("Wrapped {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Close the temporary wSOL account, returning any wSOL it holds and its rent to `owner`
+    /// as SOL
+    pub fn close_temp_account<'info>(
+        token_program: &Program<'info, Token>,
+        wsol_temp: &Account<'info, TokenAccount>,
+        owner: &Signer<'info>,
+    ) -> Result<()> {
+        let close_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: wsol_temp.to_account_info(),
+                destination: owner.to_account_info(),
+                authority: owner.to_account_info(),
+            },
+        );
+        token::close_account(close_ctx)?;
+
+        msg!("Closed temporary wSOL account {}", wsol_temp.key());
+
+        Ok(())
+    }
+}
@@ -27,7 +27,7 @@ impl OracleHelper {
         // Pyth prices come with an exponent (usually negative)
         // We need to normalize to our target decimals (e.g., 9 for SOL)
         
-        let abs_expo = expo.abs() as u32;
+        let abs_expo = expo.unsigned_abs();
         
         // Convert price to u128 for calculations
         let price_u128 = if price >= 0 {
@@ -78,8 +78,8 @@ impl OracleHelper {
         let (price_b, _conf_b, expo_b) = Self::get_price(oracle_b, max_age)?;
 
         // Normalize prices to same scale (9 decimals)
-        let normalized_price_a = Self::normalize_price(price_a, expo_a as i32, 9)?;
-        let normalized_price_b = Self::normalize_price(price_b, expo_b as i32, 9)?;
+        let normalized_price_a = Self::normalize_price(price_a, expo_a, 9)?;
+        let normalized_price_b = Self::normalize_price(price_b, expo_b, 9)?;
 
         // Calculate actual exchange rate from the swap
         let actual_rate = (amount_out as u128)
@@ -126,7 +126,7 @@ impl OracleHelper {
         let confidence_pct = (confidence as u128)
             .checked_mul(10000u128)
             .ok_or(AmmError::MathOverflow)?
-            .checked_div(price.abs() as u128)
+            .checked_div(price.unsigned_abs() as u128)
             .ok_or(AmmError::DivisionByZero)?;
 
         Ok(confidence_pct as u64)